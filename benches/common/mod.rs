@@ -0,0 +1,21 @@
+//! Provides helpers shared by the benches.
+
+use std::time::{Duration, Instant};
+
+// The generator the tests use, so every run times the same scenes.
+#[path = "../../src/testing.rs"]
+mod testing;
+
+pub use self::testing::Rng;
+
+/// Times a closure, returning how long it took and its result.
+pub fn time<R, F: FnOnce() -> R>(f: F) -> (Duration, R) {
+    let start = Instant::now();
+    let result = f();
+    (start.elapsed(), result)
+}
+
+/// A duration in milliseconds.
+pub fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1e3 + f64::from(duration.subsec_nanos()) / 1e6
+}
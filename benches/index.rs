@@ -0,0 +1,77 @@
+//! Times the obstacle index against a linear scan of every polygon, for growing numbers of
+//! obstacles.
+//!
+//! Run with `cargo bench --bench index`.
+
+extern crate convex_hull_pf;
+
+// Each bench uses only some of the helpers.
+#[allow(dead_code)]
+mod common;
+
+use common::{millis, time, Rng};
+use convex_hull_pf::shape::coord::Coord;
+use convex_hull_pf::shape::index::ObstacleIndex;
+use convex_hull_pf::shape::polygon::Polygon;
+use convex_hull_pf::shape::segment::Segment;
+
+/// The number of segments queried for each scene.
+const QUERIES: usize = 2000;
+
+/// A scene of scattered square obstacles, and segments to query against it.
+fn scene(rng: &mut Rng, obstacles: usize) -> (Vec<Polygon>, Vec<Segment>) {
+    let size = 100 * (obstacles as f64).sqrt() as i64;
+    let coord = |x, y| Coord { x, y };
+    let polygons = (0..obstacles)
+        .map(|_| {
+            let (x, y) = (rng.range(0, size - 1), rng.range(0, size - 1));
+            Polygon::from_points(vec![
+                coord(x, y),
+                coord(x + 40, y),
+                coord(x + 40, y + 40),
+                coord(x, y + 40),
+            ])
+        })
+        .collect();
+    let segments = (0..QUERIES)
+        .map(|_| {
+            let a = coord(rng.range(0, size - 1), rng.range(0, size - 1));
+            let b = coord(a.x + rng.range(-250, 249), a.y + rng.range(-250, 249));
+            Segment::from_coords(a, b)
+        })
+        .collect();
+    (polygons, segments)
+}
+
+fn main() {
+    let mut rng = Rng::new(11);
+    println!("obstacles       build       index      linear");
+    for &obstacles in &[100, 1000, 4000] {
+        let (polygons, segments) = scene(&mut rng, obstacles);
+        let (build, index) = time(|| ObstacleIndex::new(&polygons));
+        let (indexed, found) = time(|| {
+            segments
+                .iter()
+                .map(|segment| index.intersecting_polygons(segment))
+                .collect::<Vec<_>>()
+        });
+        let (linear, expected) = time(|| {
+            segments
+                .iter()
+                .map(|segment| {
+                    (0..polygons.len())
+                        .filter(|&polygon| segment.crosses_interior(&polygons[polygon]))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(found, expected);
+        println!(
+            "{:>9} {:>9.1}ms {:>9.1}ms {:>9.1}ms",
+            obstacles,
+            millis(build),
+            millis(indexed),
+            millis(linear)
+        );
+    }
+}
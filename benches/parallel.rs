@@ -0,0 +1,111 @@
+//! Times processing long routes with the hull planner.
+//!
+//! Run with `cargo bench --bench parallel` to time the plain sequential map used without the
+//! `parallel` feature, and with `cargo bench --features parallel --bench parallel` to time the
+//! legs planned by rayon on one thread, and on every available thread.
+
+extern crate convex_hull_pf;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+
+// Not every helper is used by this bench.
+#[allow(dead_code)]
+mod common;
+
+use common::{millis, time, Rng};
+use convex_hull_pf::io::input::Input;
+#[cfg(feature = "parallel")]
+use convex_hull_pf::io::output::Output;
+use convex_hull_pf::process::algorithm::QuickHull;
+use convex_hull_pf::process::process;
+use convex_hull_pf::shape::coord::Coord;
+use convex_hull_pf::shape::offset::Join;
+use convex_hull_pf::shape::polygon::Polygon;
+#[cfg(feature = "parallel")]
+use rayon::ThreadPoolBuilder;
+#[cfg(feature = "parallel")]
+use std::time::Duration;
+
+/// The number of obstacles along each side of the grid.
+const GRID: i64 = 25;
+
+/// A grid of small irregular obstacles, with a route of a number of points between them.
+fn scene(rng: &mut Rng, route: usize) -> Input {
+    let coord = |x, y| Coord { x, y };
+    let mut polygons = Vec::new();
+    for column in 0..GRID {
+        for row in 0..GRID {
+            let (x, y) = (column * 40 + 10, row * 40 + 10);
+            polygons.push(Polygon::from_points(vec![
+                coord(x, y),
+                coord(x + 8 + rng.range(0, 7), y + 2),
+                coord(x + 12, y + 8 + rng.range(0, 7)),
+                coord(x + 1, y + 10),
+            ]));
+        }
+    }
+    Input {
+        start: coord(5, 5),
+        end: coord(GRID * 40 - 5, GRID * 40 - 5),
+        route: (0..route)
+            .map(|_| coord(rng.range(0, GRID - 1) * 40 + 5, rng.range(0, GRID - 1) * 40 + 5))
+            .collect(),
+        unordered_route: false,
+        polygons,
+        clearance: 0.0,
+        join: Join::default(),
+        snap: false,
+        trace: false,
+    }
+}
+
+/// Times a closure on a thread pool of a size, returning how long it took and its result.
+#[cfg(feature = "parallel")]
+fn time_on<R: Send, F: FnOnce() -> R + Send>(threads: usize, f: F) -> (Duration, R) {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    pool.install(|| time(f))
+}
+
+/// The points of every path of an output.
+#[cfg(feature = "parallel")]
+fn path_points(output: &Output) -> Vec<Vec<Coord>> {
+    output
+        .paths
+        .iter()
+        .map(|path| path.points.clone())
+        .collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn main() {
+    let mut rng = Rng::new(20);
+    println!("  legs  sequential");
+    for &route in &[20, 80, 320] {
+        let input = scene(&mut rng, route);
+        let (sequential, _) = time(|| process(&input, &QuickHull).unwrap());
+        println!("{:>6} {:>9.1}ms", route + 1, millis(sequential));
+    }
+}
+
+#[cfg(feature = "parallel")]
+fn main() {
+    let mut rng = Rng::new(20);
+    let threads = rayon::current_num_threads();
+    println!("{} threads available", threads);
+    println!("  legs  one thread    parallel");
+    for &route in &[20, 80, 320] {
+        let input = scene(&mut rng, route);
+        let (single, expected) = time_on(1, || process(&input, &QuickHull).unwrap());
+        let (parallel, output) = time_on(threads, || process(&input, &QuickHull).unwrap());
+        assert_eq!(path_points(&output), path_points(&expected));
+        println!(
+            "{:>6} {:>9.1}ms {:>9.1}ms",
+            route + 1,
+            millis(single),
+            millis(parallel)
+        );
+    }
+}
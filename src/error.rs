@@ -0,0 +1,90 @@
+//! Provides the Error enum.
+
+use std::error;
+use std::fmt;
+
+/// The ways in which processing an input can fail.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Error {
+    /// A hull was requested over fewer than two distinct points.
+    DegenerateInput {
+        /// The number of distinct points given.
+        points: usize,
+    },
+
+    /// A polygon has fewer than three distinct points.
+    InvalidPolygon {
+        /// The index of the polygon in the input.
+        polygon: usize,
+    },
+
+    /// A polygon's boundary crosses or touches itself.
+    SelfIntersectingPolygon {
+        /// The index of the polygon in the input.
+        polygon: usize,
+        /// The indices of a pair of edges that intersect.
+        edges: (usize, usize),
+    },
+
+    /// A hole of a polygon is malformed, or does not lie strictly inside the polygon apart from
+    /// it's other holes.
+    InvalidHole {
+        /// The index of the polygon in the input.
+        polygon: usize,
+        /// The index of the hole in the polygon.
+        hole: usize,
+    },
+
+    /// A waypoint lies inside an obstacle.
+    WaypointInsideObstacle {
+        /// The index of the waypoint, counting from the start, through the route, to the end.
+        waypoint: usize,
+        /// The index of the polygon in the input.
+        polygon: usize,
+    },
+
+    /// No path could be found along a leg of the route.
+    Unreachable {
+        /// The index of the leg, counting from the start.
+        leg: usize,
+    },
+
+    /// An arithmetic operation on coordinates overflowed.
+    Overflow,
+
+    /// The clearance is negative, or not a number.
+    InvalidClearance,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::DegenerateInput { points } => {
+                write!(f, "cannot compute a hull of {} point(s)", points)
+            }
+            Error::InvalidPolygon { polygon } => {
+                write!(f, "polygon {} has fewer than three distinct points", polygon)
+            }
+            Error::SelfIntersectingPolygon { polygon, edges } => write!(
+                f,
+                "polygon {} intersects itself at edges {} and {}",
+                polygon, edges.0, edges.1
+            ),
+            Error::InvalidHole { polygon, hole } => write!(
+                f,
+                "hole {} of polygon {} is malformed or does not lie inside it",
+                hole, polygon
+            ),
+            Error::WaypointInsideObstacle { waypoint, polygon } => {
+                write!(f, "waypoint {} lies inside polygon {}", waypoint, polygon)
+            }
+            Error::Unreachable { leg } => write!(f, "no path could be found along leg {}", leg),
+            Error::Overflow => write!(f, "arithmetic overflow while computing with coordinates"),
+            Error::InvalidClearance => {
+                write!(f, "the clearance must be a distance of zero or more")
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
@@ -0,0 +1,315 @@
+//! Provides conversion between GeoJSON feature collections and the input and output structs.
+//!
+//! Obstacles are read from `Polygon` and `MultiPolygon` features, with any interior rings as holes,
+//! skipping those with a `role` of `hull`. Rings follow RFC 7946, exterior rings counterclockwise and
+//! holes clockwise with y pointing up, so each is reversed to and from the winding used internally.
+//! Waypoints are read from `Point` features with a `role` property of `start`, `end` or `route`,
+//! route points are visited in ascending order of their `order` property, then document order.
+//! The `clearance`, `join`, `snap`, `unordered_route` and `trace` of the input are read from members
+//! of the feature collection itself.
+
+use io::input::Input;
+use io::output::Output;
+use serde::de::Error;
+use serde_json::{self, Map, Value};
+use shape::coord::Coord;
+use shape::polygon::Polygon;
+use shape::scalar::Scalar;
+use std::cmp::Ordering;
+
+/// Reads an input from a GeoJSON feature collection.
+pub fn input_from_value<T: Scalar>(value: Value) -> Result<Input<T>, serde_json::Error> {
+    let mut collection = match value {
+        Value::Object(collection) => collection,
+        _ => return Err(Error::custom("expected a feature collection")),
+    };
+    let features = match collection.remove("features") {
+        Some(Value::Array(features)) => features,
+        _ => return Err(Error::custom("expected a feature collection")),
+    };
+    let clearance = match collection.remove("clearance") {
+        Some(clearance) => serde_json::from_value(clearance)?,
+        None => 0.0,
+    };
+    let join = match collection.remove("join") {
+        Some(join) => serde_json::from_value(join)?,
+        None => Default::default(),
+    };
+    let snap = match collection.remove("snap") {
+        Some(snap) => serde_json::from_value(snap)?,
+        None => false,
+    };
+    let unordered_route = match collection.remove("unordered_route") {
+        Some(unordered_route) => serde_json::from_value(unordered_route)?,
+        None => false,
+    };
+    let trace = match collection.remove("trace") {
+        Some(trace) => serde_json::from_value(trace)?,
+        None => false,
+    };
+
+    let mut start = None;
+    let mut end = None;
+    let mut route = Vec::new();
+    let mut polygons = Vec::new();
+
+    for feature in features {
+        let role = feature
+            .pointer("/properties/role")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+        let order = feature
+            .pointer("/properties/order")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+        let geometry_type = feature.pointer("/geometry/type").and_then(Value::as_str);
+        let coordinates = feature.pointer("/geometry/coordinates");
+
+        match (geometry_type, coordinates) {
+            (Some("Point"), Some(position)) => {
+                let coord = position_to_coord(position)?;
+                match role.as_deref() {
+                    Some("start") => start = Some(coord),
+                    Some("end") => end = Some(coord),
+                    Some("route") => route.push((order, coord)),
+                    _ => {}
+                }
+            }
+            // Hulls from a previous output are not obstacles.
+            _ if role.as_deref() == Some("hull") => {}
+            (Some("Polygon"), Some(rings)) => polygons.push(rings_to_polygon(rings)?),
+            (Some("MultiPolygon"), Some(Value::Array(multi))) => {
+                for rings in multi {
+                    polygons.push(rings_to_polygon(rings)?);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Stable, so points with equal order keep their document order.
+    route.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+    Ok(Input {
+        start: start.ok_or_else(|| Error::custom("missing point with role `start`"))?,
+        end: end.ok_or_else(|| Error::custom("missing point with role `end`"))?,
+        route: route.into_iter().map(|(_, coord)| coord).collect(),
+        unordered_route,
+        polygons,
+        clearance,
+        join,
+        snap,
+        trace,
+    })
+}
+
+/// Writes an output as a GeoJSON feature collection.
+///
+/// The input obstacles and waypoints are written with the same roles they are read with, so the
+/// collection can be read back as an input. Hulls are written as `Polygon` features with a `role`
+/// of `hull`, and paths as `LineString` features with a `role` of `path`, both with the index of
+/// their `leg`. Snapped waypoints are written as `Point` features with a `role` of `snap`, with the
+/// index of the `waypoint` and the `polygon` it was moved out of. The `order` an unordered route
+/// was visited in, and the `traced_legs` of a trace, are written as members of the collection.
+pub fn output_to_value<T: Scalar>(output: &Output<T>) -> Result<Value, serde_json::Error> {
+    let mut features = Vec::new();
+
+    for (index, polygon) in output.input.polygons.iter().enumerate() {
+        features.push(feature(
+            "Polygon",
+            polygon_to_rings(polygon)?,
+            vec![("role", "obstacle".into()), ("index", index.into())],
+        ));
+    }
+
+    features.push(feature(
+        "Point",
+        coord_to_position(output.input.start)?,
+        vec![("role", "start".into())],
+    ));
+    for (order, &coord) in output.input.route.iter().enumerate() {
+        features.push(feature(
+            "Point",
+            coord_to_position(coord)?,
+            vec![("role", "route".into()), ("order", order.into())],
+        ));
+    }
+    features.push(feature(
+        "Point",
+        coord_to_position(output.input.end)?,
+        vec![("role", "end".into())],
+    ));
+
+    for (leg, hull) in output.hulls.iter().enumerate() {
+        features.push(feature(
+            "Polygon",
+            polygon_to_rings(&hull.to_polygon())?,
+            vec![("role", "hull".into()), ("leg", leg.into())],
+        ));
+    }
+
+    for (leg, path) in output.paths.iter().enumerate() {
+        let line = path
+            .points
+            .iter()
+            .map(|&coord| coord_to_position(coord))
+            .collect::<Result<Vec<_>, _>>()?;
+        features.push(feature(
+            "LineString",
+            Value::Array(line),
+            vec![
+                ("role", "path".into()),
+                ("leg", leg.into()),
+                ("length", path.length.into()),
+            ],
+        ));
+    }
+
+    for snap in &output.snaps {
+        features.push(feature(
+            "Point",
+            coord_to_position(snap.point)?,
+            vec![
+                ("role", "snap".into()),
+                ("waypoint", snap.waypoint.into()),
+                ("polygon", snap.polygon.into()),
+            ],
+        ));
+    }
+
+    let mut collection = Map::new();
+    collection.insert("type".to_owned(), "FeatureCollection".into());
+    collection.insert("features".to_owned(), Value::Array(features));
+    if output.input.clearance > 0.0 {
+        collection.insert("clearance".to_owned(), output.input.clearance.into());
+        collection.insert("join".to_owned(), serde_json::to_value(output.input.join)?);
+    }
+    if output.input.snap {
+        collection.insert("snap".to_owned(), true.into());
+    }
+    if output.input.unordered_route {
+        collection.insert("unordered_route".to_owned(), true.into());
+        collection.insert("order".to_owned(), serde_json::to_value(&output.order)?);
+    }
+    if let Some(ref trace) = output.trace {
+        collection.insert("trace".to_owned(), true.into());
+        collection.insert("traced_legs".to_owned(), serde_json::to_value(&trace.legs)?);
+    }
+    Ok(Value::Object(collection))
+}
+
+/// Builds a feature from its geometry and properties.
+fn feature(geometry_type: &str, coordinates: Value, properties: Vec<(&str, Value)>) -> Value {
+    let mut geometry = Map::new();
+    geometry.insert("type".to_owned(), geometry_type.into());
+    geometry.insert("coordinates".to_owned(), coordinates);
+
+    let mut feature = Map::new();
+    feature.insert("type".to_owned(), "Feature".into());
+    feature.insert("geometry".to_owned(), Value::Object(geometry));
+    feature.insert(
+        "properties".to_owned(),
+        Value::Object(
+            properties
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value))
+                .collect(),
+        ),
+    );
+    Value::Object(feature)
+}
+
+/// Reads a coordinate from a position, ignoring any altitude.
+fn position_to_coord<T: Scalar>(position: &Value) -> Result<Coord<T>, serde_json::Error> {
+    match position.as_array().map(Vec::as_slice) {
+        Some([x, y, ..]) => Ok(Coord {
+            x: serde_json::from_value(x.clone())?,
+            y: serde_json::from_value(y.clone())?,
+        }),
+        _ => Err(Error::custom("expected a position of at least two numbers")),
+    }
+}
+
+/// Writes a coordinate as a position.
+fn coord_to_position<T: Scalar>(coord: Coord<T>) -> Result<Value, serde_json::Error> {
+    Ok(Value::Array(vec![
+        serde_json::to_value(coord.x)?,
+        serde_json::to_value(coord.y)?,
+    ]))
+}
+
+/// Writes a polygon as a list of linear rings, its exterior ring followed by its holes, each
+/// reversed to the winding of RFC 7946.
+fn polygon_to_rings<T: Scalar>(polygon: &Polygon<T>) -> Result<Value, serde_json::Error> {
+    Some(&polygon.points)
+        .into_iter()
+        .chain(polygon.holes.iter().map(|hole| &hole.points))
+        .map(|points| {
+            let mut ring = points
+                .iter()
+                .rev()
+                .map(|&coord| coord_to_position(coord))
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(first) = ring.first().cloned() {
+                ring.push(first);
+            }
+            Ok(Value::Array(ring))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::Array)
+}
+
+/// Reads a polygon from a list of linear rings, the exterior ring followed by any holes, each
+/// reversed from the winding of RFC 7946.
+fn rings_to_polygon<T: Scalar>(rings: &Value) -> Result<Polygon<T>, serde_json::Error> {
+    let mut rings = match rings.as_array() {
+        Some(rings) if !rings.is_empty() => rings.iter().map(ring_to_points),
+        _ => return Err(Error::custom("expected a polygon with an exterior ring")),
+    };
+    let points = rings.next().unwrap()?; // Guarunteed to have value
+    let holes = rings
+        .map(|ring| ring.map(Polygon::from_points))
+        .collect::<Result<_, _>>()?;
+    Ok(Polygon { points, holes })
+}
+
+/// Reads the points of a linear ring, in reverse order.
+fn ring_to_points<T: Scalar>(ring: &Value) -> Result<Vec<Coord<T>>, serde_json::Error> {
+    let mut points = ring
+        .as_array()
+        .ok_or_else(|| Error::custom("expected a linear ring"))?
+        .iter()
+        .map(position_to_coord)
+        .collect::<Result<Vec<_>, _>>()?;
+    // Linear rings repeat their first position at the end.
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    points.reverse();
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A square with a square hole, wound as RFC 7946 requires.
+    const SQUARE_WITH_HOLE: &str = "[
+        [[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]],
+        [[2, 2], [2, 8], [8, 8], [8, 2], [2, 2]]
+    ]";
+
+    #[test]
+    fn rfc_7946_rings_read_as_valid_polygons() {
+        let rings = serde_json::from_str(SQUARE_WITH_HOLE).unwrap();
+        let polygon = rings_to_polygon::<i64>(&rings).unwrap();
+        assert!(polygon.validate().is_valid());
+    }
+
+    #[test]
+    fn rings_are_written_as_they_are_read() {
+        let rings: Value = serde_json::from_str(SQUARE_WITH_HOLE).unwrap();
+        let polygon = rings_to_polygon::<i64>(&rings).unwrap();
+        assert_eq!(polygon_to_rings(&polygon).unwrap(), rings);
+    }
+}
@@ -1,22 +1,46 @@
 //! Provides the input struct.
 
 use shape::coord::Coord;
+use shape::offset::Join;
 use shape::polygon::Polygon;
 
 /// The input for deserialization.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Input {
+pub struct Input<T = i64> {
     /// The start of the path.
-    pub start: Coord,
+    pub start: Coord<T>,
 
     /// The end of the path.
-    pub end: Coord,
+    pub end: Coord<T>,
 
     /// Points that must be passed in order from start to end.
     #[serde(default = "Vec::new")]
-    pub route: Vec<Coord>,
+    pub route: Vec<Coord<T>>,
+
+    /// Whether the route may be passed in any order, which is then chosen to make the path
+    /// shortest.
+    #[serde(default)]
+    pub unordered_route: bool,
 
     /// The polygons that block the path.
     #[serde(rename = "polygon", default = "Vec::new")]
-    pub polygons: Vec<Polygon>,
+    pub polygons: Vec<Polygon<T>>,
+
+    /// The distance to keep from every polygon, which are inflated by it before routing. Must not
+    /// be negative.
+    #[serde(default)]
+    pub clearance: f64,
+
+    /// How the inflated polygons are joined around their corners.
+    #[serde(default)]
+    pub join: Join,
+
+    /// Whether waypoints inside a polygon are moved to the nearest point on it's boundary,
+    /// rather than failing.
+    #[serde(default)]
+    pub snap: bool,
+
+    /// Whether the steps taken by the hull planner to grow each hull are recorded in the output.
+    #[serde(default)]
+    pub trace: bool,
 }
@@ -1,4 +1,5 @@
 //! Provides input and output serialization functionality.
 
+pub mod geojson;
 pub mod input;
 pub mod output;
@@ -1,14 +1,93 @@
 //! Provides the Output struct.
 
 use shape::hull::Hull;
+use shape::path::Path;
 use io::input::Input;
+use shape::polygon::Polygon;
+use shape::coord::Coord;
 
 /// The Output of computation.
 #[derive(Serialize, Deserialize, Debug)]
-pub struct Output {
+pub struct Output<T = i64> {
     /// The input that generated this output, if known
-    pub input: Input,
+    pub input: Input<T>,
+
+    /// The polygons as they were routed around, normalized, inflated by the clearance, and merged
+    /// where they overlap.
+    #[serde(rename = "obstacle")]
+    pub obstacles: Vec<Polygon<T>>,
 
     /// The point to point hulls that make up the outputs along the path.
-    pub hulls: Vec<Hull>,
+    pub hulls: Vec<Hull<T>>,
+
+    /// The shortest paths along each hull, from it's origin to it's destination.
+    pub paths: Vec<Path<T>>,
+
+    /// The waypoints that were moved out of a polygon, in the order they were moved.
+    #[serde(
+        rename = "snap",
+        default = "Vec::new",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub snaps: Vec<Snap<T>>,
+
+    /// The indices of the route points in the order they were visited, when the route is
+    /// unordered.
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<usize>,
+
+    /// The steps taken to grow the hull of each leg, when the input asks for them to be traced.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Trace<T>>,
+}
+
+/// A waypoint moved out of a polygon it was inside.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Snap<T = i64> {
+    /// The index of the waypoint, counting from the start, through the route, to the end.
+    pub waypoint: usize,
+
+    /// The index of the polygon in the input.
+    pub polygon: usize,
+
+    /// Where the waypoint was moved to.
+    pub point: Coord<T>,
+}
+
+/// The steps taken by the hull planner to grow the hull of each leg.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Trace<T = i64> {
+    /// The legs, in the order they are travelled.
+    #[serde(rename = "leg")]
+    pub legs: Vec<TracedLeg<T>>,
+}
+
+/// The steps taken to grow the hull of a leg, until it stops meeting new obstacles.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TracedLeg<T = i64> {
+    /// Where the leg starts.
+    pub origin: Coord<T>,
+
+    /// Where the leg ends.
+    pub destination: Coord<T>,
+
+    /// Each hull built for the leg, the first around the obstacles crossed by the leg itself, and
+    /// the last being the hull that was kept.
+    #[serde(rename = "iteration")]
+    pub iterations: Vec<Iteration<T>>,
+}
+
+/// A hull built while growing the hull of a leg.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Iteration<T = i64> {
+    /// The indices of the obstacles the hull passes through that add new points, which are
+    /// included in the next hull. Empty for the hull that was kept.
+    pub intersected: Vec<usize>,
+
+    /// The points the hull was built around.
+    #[serde(rename = "polypoint")]
+    pub polypoints: Vec<Coord<T>>,
+
+    /// The hull.
+    pub hull: Hull<T>,
 }
@@ -7,8 +7,20 @@
 extern crate serde_derive;
 
 extern crate image;
+#[cfg(feature = "parallel")]
+extern crate rayon;
 extern crate serde;
+extern crate serde_json;
 
+#[cfg(test)]
+extern crate num_bigint;
+
+pub mod error;
 pub mod io;
 pub mod process;
 pub mod shape;
+
+#[cfg(test)]
+mod testing;
+
+pub use error::Error;
@@ -2,28 +2,47 @@
 extern crate clap;
 
 extern crate convex_hull_pf;
+extern crate gif;
 extern crate image;
 extern crate serde_json;
 extern crate toml;
 
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, SubCommand};
+use std::io;
 use std::io::Read;
 use std::fs::File;
+use convex_hull_pf::Error;
+use convex_hull_pf::io::geojson;
 use convex_hull_pf::io::input::Input;
-use convex_hull_pf::io::output::Output;
+use convex_hull_pf::io::output::{Iteration, Output};
+use convex_hull_pf::process::algorithm::{Chan, GrahamScan, MonotoneChain, QuickHull};
 use convex_hull_pf::process::process;
+use convex_hull_pf::process::visibility;
+use convex_hull_pf::shape::containment::Containment;
+use convex_hull_pf::shape::coord::Coord;
+use convex_hull_pf::shape::hull::Hull;
+use convex_hull_pf::shape::orientation::Orientation;
+use convex_hull_pf::shape::path::Path;
+use convex_hull_pf::shape::polygon::Polygon;
+use convex_hull_pf::shape::scalar::Scalar;
+use convex_hull_pf::shape::segment::Segment;
 use std::io::Write;
 use std::io::Cursor;
 use image::ImageBuffer;
 use image::GenericImage;
 use image::png::PNGEncoder;
+use gif::Encoder as GIFEncoder;
+use gif::Frame as GIFFrame;
+use gif::{Repeat, SetParameter};
 use image::RGB;
 use image::Rgb;
 use image::Pixel;
+use std::borrow::Cow;
+use std::collections::HashMap;
 
 macro_rules! hard_crash {
     ($code:expr, $($arg:tt)*) => {{
-        println!($($arg)*);
+        eprintln!($($arg)*);
         $crate::std::process::exit($code);
     }}
 }
@@ -33,69 +52,242 @@ fn main() {
         .version(crate_version!())
         .author(crate_authors!())
         .about("Finds a path along a route using a convex hull algorithm.")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(
             Arg::with_name("INPUT")
-                .help("The input to process")
+                .help("The input to process, or \"-\" to read from stdin")
                 .required(true)
                 .index(1),
         )
         .arg(
             Arg::with_name("OUTPUT")
-                .help("The file to output to")
+                .help("The file to output to, or \"-\" to write to stdout")
                 .required(true)
                 .index(2),
         )
+        .arg(
+            Arg::with_name("input-format")
+                .help(
+                    "Specify the input format, \"toml\" or \"json\" or \"geojson\", detected from the file extension or contents by default",
+                )
+                .short("i")
+                .long("input-format")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("output")
-                .help("Specify the output mode, \"toml\" or \"json\" or \"png\"")
+                .help(
+                    "Specify the output mode, \"toml\" or \"json\" or \"geojson\" or \"png\" or \"svg\" or \"gif\"",
+                )
                 .short("o")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("planner")
+                .help("Specify the path planner, \"hull\" or \"visibility\"")
+                .short("p")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("algorithm")
+                .help(
+                    "Specify the convex hull algorithm, \"quickhull\" or \"monotone-chain\" or \"graham\" or \"chan\", only valid with the \"hull\" planner",
+                )
+                .short("a")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("coordinates")
+                .help("Specify the coordinate type, \"i64\" or \"i32\" or \"f64\"")
+                .short("c")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("trace")
+                .help(
+                    "Record each hull built while growing the hull of each leg in the output, and in \"png\" or \"svg\" mode also write one frame per hull, named after the output file, only valid with the \"hull\" planner",
+                )
+                .short("t")
+                .long("trace"),
+        )
         .arg(
             Arg::with_name("output-scale")
-                .help("Specify the output scale, only valid in \"png\" mode")
+                .help("Specify the output scale, only valid in \"png\" or \"gif\" mode")
                 .short("s")
+                .takes_value(true)
+                .validator(|scale| parse_scale(&scale).map(|_| ())),
+        )
+        .arg(
+            Arg::with_name("output-padding")
+                .help("Specify the padding in pixels, only valid in \"png\" or \"gif\" mode")
+                .short("m")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output-max-size")
+                .help(
+                    "Specify the maximum width and height in pixels, only valid in \"png\" or \"gif\" mode",
+                )
+                .short("x")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("frame-delay")
+                .help("Specify the delay between frames in milliseconds, only valid in \"gif\" mode")
+                .short("d")
+                .long("frame-delay")
+                .takes_value(true),
+        )
+        .subcommand(
+            SubCommand::with_name("validate")
+                .about("Reports problems with the polygons of an input, and waypoints inside them.")
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The input to validate, or \"-\" to read from stdin")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("input-format")
+                        .help("Specify the input format, \"toml\" or \"json\" or \"geojson\"")
+                        .short("i")
+                        .long("input-format")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("coordinates")
+                        .help("Specify the coordinate type, \"i64\" or \"i32\" or \"f64\"")
+                        .short("c")
+                        .takes_value(true),
+                ),
+        )
         .get_matches();
 
+    if let Some(matches) = matches.subcommand_matches("validate") {
+        // Unwrap is safe as CLAP handles requirement of value.
+        let input_file = matches.value_of("INPUT").unwrap();
+        let buf = read_input_file(input_file);
+        let format = input_format(&buf, input_file, matches.value_of("input-format"));
+        let valid = match matches.value_of("coordinates").unwrap_or("i64") {
+            "i64" => validate::<i64>(&buf, input_file, format),
+            "i32" => validate::<i32>(&buf, input_file, format),
+            "f64" => validate::<f64>(&buf, input_file, format),
+            coordinates => hard_crash!(1, "Invalid coordinate type `{}`", coordinates),
+        };
+        if !valid {
+            ::std::process::exit(VALIDATION_FAILED);
+        }
+        return;
+    }
+
     // Unwrap is safe as CLAP handles requirement of value.
     let input_file = matches.value_of("INPUT").unwrap();
     let output_file = matches.value_of("OUTPUT").unwrap();
 
     let mode = matches.value_of("output").unwrap_or("toml");
-    let scale: u32 = matches
-        .value_of("output-scale")
-        .unwrap_or("1")
-        .parse()
-        .unwrap_or(1);
+    let planner = matches.value_of("planner").unwrap_or("hull");
+    let algorithm = matches.value_of("algorithm").unwrap_or("quickhull");
+    let coordinates = matches.value_of("coordinates").unwrap_or("i64");
+    let trace = matches.is_present("trace");
+    let render = Render {
+        // Unwrap is safe as CLAP validates the scale.
+        scale: parse_scale(matches.value_of("output-scale").unwrap_or("1")).unwrap(),
+        padding: matches
+            .value_of("output-padding")
+            .unwrap_or("10")
+            .parse()
+            .unwrap_or(10),
+        max_size: matches
+            .value_of("output-max-size")
+            .unwrap_or("8192")
+            .parse()
+            .unwrap_or(8192),
+        delay: matches
+            .value_of("frame-delay")
+            .unwrap_or("500")
+            .parse()
+            .unwrap_or(500),
+    };
+
+    let buf = read_input_file(input_file);
+    let format = input_format(&buf, input_file, matches.value_of("input-format"));
+    let options = Options {
+        planner,
+        algorithm,
+        mode,
+        trace,
+    };
+    let (data, frames) = match coordinates {
+        "i64" => run::<i64>(&buf, input_file, format, &options, &render),
+        "i32" => run::<i32>(&buf, input_file, format, &options, &render),
+        "f64" => run::<f64>(&buf, input_file, format, &options, &render),
+        coordinates => hard_crash!(1, "Invalid coordinate type `{}`", coordinates),
+    };
+    if !frames.is_empty() && output_file == "-" {
+        hard_crash!(1, "Cannot write trace frames when writing to stdout");
+    }
+    write_output_file(output_file, &data);
+    for (leg, iteration, frame) in frames {
+        write_output_file(&frame_file(output_file, leg, iteration), &frame);
+    }
+}
 
+/// Writes data to the output file, writing stdout if the file is `-`.
+fn write_output_file(output_file: &str, data: &[u8]) {
+    if output_file == "-" {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        if let Err(e) = stdout.write_all(data).and_then(|_| stdout.flush()) {
+            hard_crash!(1, "Error Writing to stdout :: `{}`", e);
+        }
+        return;
+    }
+    match File::create(output_file) {
+        Ok(mut file) => {
+            if let Err(e) = file.write_all(data) {
+                hard_crash!(1, "Error Writing to `{}` :: `{}`", output_file, e);
+            }
+            if let Err(e) = file.flush() {
+                hard_crash!(1, "Error Flushing `{}` :: `{}`", output_file, e);
+            }
+        }
+        Err(e) => {
+            hard_crash!(1, "Error Opening `{}` :: `{}`", output_file, e);
+        }
+    }
+}
+
+/// Names the file a trace frame is written to, by adding the leg and iteration to the output file
+/// before it's extension, so `out.png` becomes `out-0-2.png`.
+fn frame_file(output_file: &str, leg: usize, iteration: usize) -> String {
+    let name_start = output_file.rfind('/').map(|slash| slash + 1).unwrap_or(0);
+    let extension = match output_file[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => name_start + dot,
+        _ => output_file.len(),
+    };
+    format!(
+        "{}-{}-{}{}",
+        &output_file[..extension],
+        leg,
+        iteration,
+        &output_file[extension..]
+    )
+}
+
+/// Reads the input file to a string, reading stdin if the file is `-`.
+fn read_input_file(input_file: &str) -> String {
+    if input_file == "-" {
+        let mut buf = String::new();
+        if let Err(e) = io::stdin().read_to_string(&mut buf) {
+            hard_crash!(1, "Error reading stdin :: `{}`", e);
+        }
+        return buf;
+    }
     match File::open(input_file) {
         Ok(mut file) => {
             let mut buf = String::new();
             match file.read_to_string(&mut buf) {
-                Ok(_) => {
-                    let input = text_to_input(&buf, input_file);
-                    let output = input_to_output(&input);
-                    match File::create(output_file) {
-                        Ok(mut file) => {
-                            if let Err(e) = match mode {
-                                "toml" => file.write(&output_to_toml(&output)),
-                                "json" => file.write(&output_to_json(&output)),
-                                "png" => file.write(&output_to_png(&output, scale)),
-                                mode => hard_crash!(1, "Invalid output mode `{}`", mode),
-                            } {
-                                hard_crash!(1, "Error Writing to `{}` :: `{}`", output_file, e);
-                            }
-                            if let Err(e) = file.flush() {
-                                hard_crash!(1, "Error Flushing `{}` :: `{}`", output_file, e);
-                            }
-                        }
-                        Err(e) => {
-                            hard_crash!(1, "Error Opening `{}` :: `{}`", output_file, e);
-                        }
-                    }
-                }
+                Ok(_) => buf,
                 Err(e) => hard_crash!(1, "Error reading `{}` :: `{}`", input_file, e),
             }
         }
@@ -105,61 +297,386 @@ fn main() {
     }
 }
 
+/// Validates the polygons of the input text, printing any problems found, including waypoints that
+/// lie inside them. Returns true if all polygons are valid.
+fn validate<T: Scalar>(buf: &str, input_file: &str, format: &str) -> bool {
+    let input = text_to_input::<T>(buf, input_file, format);
+    let mut valid = true;
+    for (index, polygon) in input.polygons.iter().enumerate() {
+        let report = polygon.validate();
+        if report.is_valid() {
+            continue;
+        }
+        valid = false;
+        if report.too_few_points {
+            println!("polygon {}: fewer than three points", index);
+        }
+        match report.winding {
+            Orientation::Counterclockwise => {}
+            Orientation::Clockwise => println!("polygon {}: clockwise winding", index),
+            Orientation::Colinear => println!("polygon {}: encloses no area", index),
+        }
+        for vertex in &report.duplicate_vertices {
+            println!("polygon {}: duplicate vertex {}", index, vertex);
+        }
+        for vertex in &report.colinear_spikes {
+            println!("polygon {}: colinear spike at vertex {}", index, vertex);
+        }
+        for &(first, second) in &report.self_intersections {
+            println!("polygon {}: edges {} and {} intersect", index, first, second);
+        }
+        for hole in &report.unnormalized_holes {
+            println!("polygon {}: hole {} is not normalized", index, hole);
+        }
+        for hole in &report.invalid_holes {
+            println!("polygon {}: hole {} is malformed or misplaced", index, hole);
+        }
+        if report.is_unfixable() {
+            println!("polygon {}: cannot be fixed automatically", index);
+        }
+    }
+    // Only the polygons as given are checked, before they are inflated by any clearance.
+    let waypoints = Some(input.start)
+        .into_iter()
+        .chain(input.route.iter().cloned())
+        .chain(Some(input.end));
+    for (waypoint, coord) in waypoints.enumerate() {
+        for (polygon, shape) in input.polygons.iter().enumerate() {
+            if shape.contains(coord) == Containment::Inside {
+                valid = false;
+                println!("waypoint {}: inside polygon {}", waypoint, polygon);
+            }
+        }
+    }
+    if valid {
+        println!("all {} polygons are valid", input.polygons.len());
+    }
+    valid
+}
+
+/// Runs the input text through the chosen planner with the chosen coordinate type, encoding the
+/// output in the chosen mode.
+///
+/// Returns the encoded output, and in "png" and "svg" mode the frames of any trace.
+fn run<T: Scalar>(
+    buf: &str,
+    input_file: &str,
+    format: &str,
+    options: &Options,
+    render: &Render,
+) -> (Vec<u8>, EncodedFrames) {
+    let mut input = text_to_input::<T>(buf, input_file, format);
+    // An animation shows the hulls of a trace growing.
+    input.trace = input.trace || options.trace || options.mode == "gif";
+    let output = input_to_output(&input, options.planner, options.algorithm, input_file);
+    let bounds = bounding_box(&output);
+    let data = match options.mode {
+        "toml" => output_to_toml(&output),
+        "json" => output_to_json(&output),
+        "geojson" => output_to_geojson(&output),
+        "png" => output_to_png(&output, None, bounds, render),
+        "svg" => output_to_svg(&output, None, bounds),
+        "gif" => output_to_gif(&output, bounds, render),
+        mode => hard_crash!(1, "Invalid output mode `{}`", mode),
+    };
+
+    // Frames share the bounds of the whole output, so they line up.
+    let mut frames = Vec::new();
+    if let Some(ref trace) = output.trace {
+        for (leg, traced) in trace.legs.iter().enumerate() {
+            for (index, iteration) in traced.iterations.iter().enumerate() {
+                let frame = Frame {
+                    leg,
+                    iteration: Some(iteration),
+                };
+                let frame = match options.mode {
+                    "png" => output_to_png(&output, Some(&frame), bounds, render),
+                    "svg" => output_to_svg(&output, Some(&frame), bounds),
+                    _ => continue,
+                };
+                frames.push((leg, index, frame));
+            }
+        }
+    }
+    (data, frames)
+}
+
+/// Parses the output scale, which must be a positive finite number of pixels per unit.
+fn parse_scale(scale: &str) -> Result<f64, String> {
+    match scale.parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => Ok(value),
+        _ => Err(format!(
+            "the output scale must be a positive finite number, not `{}`",
+            scale
+        )),
+    }
+}
+
+/// Chooses the format of the input text, from the explicit format if given, then the file
+/// extension, and finally by sniffing the contents.
+fn input_format<'a>(input: &str, input_file: &str, explicit: Option<&'a str>) -> &'a str {
+    if let Some(format) = explicit {
+        return format;
+    }
+    if input_file.ends_with(".toml") {
+        "toml"
+    } else if input_file.ends_with(".geojson") {
+        "geojson"
+    } else if input_file.ends_with(".json") {
+        "json"
+    } else if !input.trim_start().starts_with('{') {
+        "toml"
+    } else if input.contains("\"FeatureCollection\"") {
+        "geojson"
+    } else {
+        "json"
+    }
+}
+
 /// Processes the input text file, turning it into an input serial object.
-fn text_to_input(input: &str, input_file: &str) -> Input {
-    match toml::from_str(input) {
+fn text_to_input<T: Scalar>(input: &str, input_file: &str, format: &str) -> Input<T> {
+    let parsed = match format {
+        "toml" => toml::from_str(input).map_err(|e| e.to_string()),
+        "json" => serde_json::from_str(input).map_err(|e| e.to_string()),
+        "geojson" => serde_json::from_str(input)
+            .and_then(geojson::input_from_value)
+            .map_err(|e| e.to_string()),
+        format => hard_crash!(1, "Invalid input format `{}`", format),
+    };
+    match parsed {
         Ok(input) => input,
         Err(e) => hard_crash!(1, "Error parsing `{}` :: `{}`", input_file, e),
     }
 }
 
-/// Processes the input, converting it to the output.
-fn input_to_output(input: &Input) -> Output {
-    process(input)
+/// Processes the input, converting it to the output with the chosen planner and hull algorithm.
+fn input_to_output<T: Scalar>(
+    input: &Input<T>,
+    planner: &str,
+    algorithm: &str,
+    input_file: &str,
+) -> Output<T> {
+    let output = match (planner, algorithm) {
+        ("hull", "quickhull") => process(input, &QuickHull),
+        ("hull", "monotone-chain") => process(input, &MonotoneChain),
+        ("hull", "graham") => process(input, &GrahamScan),
+        ("hull", "chan") => process(input, &Chan),
+        ("hull", algorithm) => hard_crash!(1, "Invalid hull algorithm `{}`", algorithm),
+        ("visibility", _) => visibility::process(input),
+        (planner, _) => hard_crash!(1, "Invalid planner `{}`", planner),
+    };
+    match output {
+        Ok(output) => output,
+        Err(e) => hard_crash!(
+            error_code(&e),
+            "Error processing `{}` :: `{}`",
+            input_file,
+            e
+        ),
+    }
+}
+
+/// The exit code of the validate subcommand when it finds a problem, the same as for an invalid
+/// polygon found while processing.
+const VALIDATION_FAILED: i32 = 3;
+
+/// The exit code the process should use when failing with an error.
+fn error_code(error: &Error) -> i32 {
+    match *error {
+        Error::DegenerateInput { .. } => 2,
+        Error::InvalidPolygon { .. } => VALIDATION_FAILED,
+        Error::SelfIntersectingPolygon { .. } => 7,
+        Error::InvalidHole { .. } => 8,
+        Error::WaypointInsideObstacle { .. } => 4,
+        Error::Unreachable { .. } => 5,
+        Error::Overflow => 6,
+        Error::InvalidClearance => 9,
+    }
 }
 
 /// Converts the output to a toml binary encoded text format.
-fn output_to_toml(output: &Output) -> Vec<u8> {
+fn output_to_toml<T: Scalar>(output: &Output<T>) -> Vec<u8> {
     format!("{}", toml::Value::try_from(output).unwrap()).into_bytes()
 }
 
 /// Converts the output to a json binary encoded text format.
-fn output_to_json(output: &Output) -> Vec<u8> {
+fn output_to_json<T: Scalar>(output: &Output<T>) -> Vec<u8> {
     serde_json::to_string(output).unwrap().into_bytes()
 }
 
-/// Converts the output to a png binary format.
-fn output_to_png(output: &Output, scale: u32) -> Vec<u8> {
-    let (x_size, y_size) = Some(output.input.start)
-        .into_iter()
-        .chain(Some(output.input.end).into_iter())
-        .chain(output.input.route.clone().into_iter())
-        .chain(
-            output
-                .input
-                .polygons
-                .clone()
-                .into_iter()
-                .flat_map(|polygon| polygon.points.into_iter()),
-        )
-        .fold((0, 0), |(mut max_x, mut max_y), coord| {
-            if coord.x > max_x {
-                max_x = coord.x;
-            }
-            if coord.y > max_y {
-                max_y = coord.y;
+/// Converts the output to a GeoJSON binary encoded text format.
+fn output_to_geojson<T: Scalar>(output: &Output<T>) -> Vec<u8> {
+    serde_json::to_string(&geojson::output_to_value(output).unwrap())
+        .unwrap()
+        .into_bytes()
+}
+
+/// Converts the output to a png binary format, fitting the bounds into the image.
+///
+/// A frame of a trace is drawn as in `draw_image`.
+fn output_to_png<T: Scalar>(
+    output: &Output<T>,
+    frame: Option<&Frame<T>>,
+    bounds: (f64, f64, f64, f64),
+    render: &Render,
+) -> Vec<u8> {
+    let raster = Raster::fit(bounds, render);
+    let image = draw_image(output, frame, &[], &raster);
+
+    // Return png data
+    let mut buf = Vec::new();
+    PNGEncoder::new(Cursor::new(&mut buf))
+        .encode(&image.into_vec(), raster.width, raster.height, RGB(8))
+        .unwrap();
+    buf
+}
+
+/// Converts the output to an animated gif, fitting the bounds into every frame.
+///
+/// Each leg is shown in turn, first it's hull growing one frame at a time, as traced, then the
+/// finished hull with it's path. The animation repeats forever.
+fn output_to_gif<T: Scalar>(
+    output: &Output<T>,
+    bounds: (f64, f64, f64, f64),
+    render: &Render,
+) -> Vec<u8> {
+    let raster = Raster::fit(bounds, render);
+    if raster.width > u32::from(u16::MAX) || raster.height > u32::from(u16::MAX) {
+        hard_crash!(
+            1,
+            "Cannot fit a {}x{} image in a gif",
+            raster.width,
+            raster.height
+        );
+    }
+
+    let mut images = Vec::new();
+    for leg in 0..output.paths.len() {
+        let iterations = output
+            .trace
+            .as_ref()
+            .and_then(|trace| trace.legs.get(leg))
+            .map(|traced| &traced.iterations[..])
+            .unwrap_or(&[]);
+        for iteration in iterations {
+            let frame = Frame {
+                leg,
+                iteration: Some(iteration),
+            };
+            images.push(draw_image(
+                output,
+                Some(&frame),
+                &output.paths[..leg],
+                &raster,
+            ));
+        }
+        let frame = Frame {
+            leg,
+            iteration: None,
+        };
+        images.push(draw_image(
+            output,
+            Some(&frame),
+            &output.paths[..(leg + 1)],
+            &raster,
+        ));
+    }
+    if images.is_empty() {
+        images.push(draw_image(output, None, &output.paths, &raster));
+    }
+
+    // Gif delays are in hundredths of a second.
+    let delay = (render.delay / 10).min(u32::from(u16::MAX)) as u16;
+    let mut buf = Vec::new();
+    {
+        let mut encoder =
+            GIFEncoder::new(&mut buf, raster.width as u16, raster.height as u16, &[]).unwrap();
+        encoder.set(Repeat::Infinite).unwrap();
+        for image in images {
+            let frame = GIFFrame {
+                delay,
+                ..gif_frame(image)
+            };
+            encoder.write_frame(&frame).unwrap();
+        }
+        // The trailer is written as the encoder is dropped.
+    }
+    buf
+}
+
+/// Converts an image into a gif frame, with a palette of it's exact colors if there are few
+/// enough, otherwise quantized.
+fn gif_frame(image: ImageBuffer<Rgb<u8>, Vec<u8>>) -> GIFFrame<'static> {
+    let (width, height) = (image.width() as u16, image.height() as u16);
+    let pixels = image.into_vec();
+    let mut palette = Vec::new();
+    let mut indices = HashMap::new();
+    let mut buffer = Vec::with_capacity(pixels.len() / 3);
+    let mut previous = None;
+    for pixel in pixels.chunks(3) {
+        // Most pixels are the same color as the one before.
+        let index = match previous {
+            Some((color, index)) if color == pixel => index,
+            _ => {
+                let next = indices.len();
+                let index = *indices.entry(pixel).or_insert(next);
+                if index == next {
+                    if next == 256 {
+                        return GIFFrame::from_rgb(width, height, &pixels);
+                    }
+                    palette.extend_from_slice(pixel);
+                }
+                previous = Some((pixel, index));
+                index
             }
-            (max_x, max_y)
-        });
+        };
+        buffer.push(index as u8);
+    }
+    GIFFrame {
+        width,
+        height,
+        palette: Some(palette),
+        buffer: Cow::Owned(buffer),
+        ..GIFFrame::default()
+    }
+}
 
+/// Draws the output onto an image, with the paths given drawn over the hulls.
+///
+/// A frame of a trace replaces the hull of it's leg, and those after it are left out, with the
+/// obstacles it passes through and the points it was built around highlighted.
+fn draw_image<T: Scalar>(
+    output: &Output<T>,
+    frame: Option<&Frame<T>>,
+    paths: &[Path<T>],
+    raster: &Raster,
+) -> ImageBuffer<Rgb<u8>, Vec<u8>> {
     let mut image: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_pixel(
-        (x_size + 20) as u32,
-        (y_size + 20) as u32,
+        raster.width,
+        raster.height,
         Rgb {
             data: [255, 255, 255],
         },
     );
 
+    // Draw Clearance
+    if output.input.clearance > 0.0 {
+        for segment in output
+            .obstacles
+            .iter()
+            .flat_map(|polygon| polygon.segments())
+        {
+            draw_segment(
+                raster,
+                &mut image,
+                segment,
+                Rgb {
+                    data: [160, 160, 160],
+                },
+            );
+        }
+    }
+
     // Draw Polygons
     for segment in output
         .input
@@ -167,87 +684,446 @@ fn output_to_png(output: &Output, scale: u32) -> Vec<u8> {
         .iter()
         .flat_map(|polygon| polygon.segments())
     {
-        bresenham_line(
-            segment.a.x,
-            segment.a.y,
-            segment.b.x,
-            segment.b.y,
-            &mut image,
-            10,
-            Rgb { data: [0, 0, 0] },
-        );
+        draw_segment(raster, &mut image, segment, Rgb { data: [0, 0, 0] });
     }
 
     // Draw Hulls
-    for segment in output.hulls.iter().flat_map(|hull| hull.segment_set.iter()) {
-        bresenham_line(
-            segment.a.x,
-            segment.a.y,
-            segment.b.x,
-            segment.b.y,
+    for segment in frame_hulls(output, frame)
+        .into_iter()
+        .flat_map(|hull| hull.segments())
+    {
+        draw_segment(
+            raster,
             &mut image,
-            10,
+            segment,
             Rgb {
                 data: [255, 0, 255],
             },
         );
     }
 
+    // Draw Paths
+    for segment in paths.iter().flat_map(|path| {
+        path.points
+            .windows(2)
+            .map(|pair| Segment::from_coords(pair[0], pair[1]))
+    }) {
+        draw_segment(
+            raster,
+            &mut image,
+            segment,
+            Rgb {
+                data: [255, 128, 0],
+            },
+        );
+    }
+
     // Draw Polypoints
     for point in output
         .input
         .polygons
         .iter()
+        .flat_map(|polygon| Some(polygon).into_iter().chain(polygon.holes.iter()))
         .flat_map(|polygon| polygon.points.iter())
     {
-        image.put_pixel(
-            point.x as u32 + 10,
-            point.y as u32 + 10,
-            Rgb { data: [0, 0, 255] },
-        );
+        draw_point(raster, &mut image, *point, Rgb { data: [0, 0, 255] });
+    }
+
+    // Draw Trace
+    if let Some(iteration) = frame.and_then(|frame| frame.iteration) {
+        for segment in iteration
+            .intersected
+            .iter()
+            .flat_map(|&polygon| output.obstacles[polygon].segments())
+        {
+            draw_segment(
+                raster,
+                &mut image,
+                segment,
+                Rgb {
+                    data: [255, 128, 0],
+                },
+            );
+        }
+        for point in &iteration.polypoints {
+            draw_point(
+                raster,
+                &mut image,
+                *point,
+                Rgb {
+                    data: [255, 0, 255],
+                },
+            );
+        }
     }
 
     // Draw Route
     for point in &output.input.route {
-        image.put_pixel(
-            point.x as u32 + 10,
-            point.y as u32 + 10,
-            Rgb { data: [128, 0, 0] },
-        )
+        draw_point(raster, &mut image, *point, Rgb { data: [128, 0, 0] });
     }
 
     // Draw Start
-    image.put_pixel(
-        output.input.start.x as u32 + 10,
-        output.input.start.y as u32 + 10,
+    draw_point(
+        raster,
+        &mut image,
+        output.input.start,
         Rgb { data: [0, 255, 0] },
     );
 
     // Draw End
-    image.put_pixel(
-        output.input.end.x as u32 + 10,
-        output.input.end.y as u32 + 10,
+    draw_point(
+        raster,
+        &mut image,
+        output.input.end,
         Rgb { data: [255, 0, 0] },
     );
 
-    // Scale image up (nearest neighbour)
-    let scaled_image: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(
-        (x_size + 20) as u32 * scale,
-        (y_size + 20) as u32 * scale,
-        |x, y| *image.get_pixel(x / scale, y / scale),
-    );
+    image
+}
 
-    // Return png data
-    let mut buf = Vec::new();
-    PNGEncoder::new(Cursor::new(&mut buf))
-        .encode(
-            &scaled_image.into_vec(),
-            (x_size + 20) as u32 * scale,
-            (y_size + 20) as u32 * scale,
-            RGB(8),
+/// The options controlling how the input is processed and the output encoded.
+struct Options<'a> {
+    /// The path planner, "hull" or "visibility".
+    planner: &'a str,
+
+    /// The convex hull algorithm used by the "hull" planner.
+    algorithm: &'a str,
+
+    /// The output mode.
+    mode: &'a str,
+
+    /// Whether to trace the hull planner, even if the input does not ask to.
+    trace: bool,
+}
+
+/// One hull built while growing the hull of a leg, drawn in place of the finished hull and path of
+/// that leg and those after it.
+struct Frame<'a, T: 'a> {
+    /// The index of the leg.
+    leg: usize,
+
+    /// The hull built, with the points it was built around and the obstacles it passes through, or
+    /// `None` to draw the finished hull of the leg.
+    iteration: Option<&'a Iteration<T>>,
+}
+
+/// The encoded frames of a trace, each with the index of it's leg and iteration.
+type EncodedFrames = Vec<(usize, usize, Vec<u8>)>;
+
+/// The options controlling how raster images are rendered.
+struct Render {
+    /// The number of pixels per unit of the coordinate space.
+    scale: f64,
+
+    /// The number of pixels of blank space around the drawing.
+    padding: u32,
+
+    /// The largest width or height of the image, the scale is reduced to fit within it.
+    max_size: u32,
+
+    /// The number of milliseconds each frame of an animation is shown for.
+    delay: u32,
+}
+
+/// A mapping from the coordinate space onto the pixels of a raster image.
+struct Raster {
+    /// The smallest x coordinate drawn.
+    min_x: f64,
+
+    /// The smallest y coordinate drawn.
+    min_y: f64,
+
+    /// The number of pixels per unit of the coordinate space.
+    scale: f64,
+
+    /// The number of pixels of blank space around the drawing.
+    padding: u32,
+
+    /// The width of the image in pixels.
+    width: u32,
+
+    /// The height of the image in pixels.
+    height: u32,
+}
+
+impl Raster {
+    /// Fits a bounding box, as `(min_x, min_y, max_x, max_y)`, into an image.
+    fn fit(bounds: (f64, f64, f64, f64), render: &Render) -> Raster {
+        let (min_x, min_y, max_x, max_y) = bounds;
+        let padding = render.padding.min(render.max_size / 4);
+        let available = f64::from(render.max_size.saturating_sub(padding * 2 + 1).max(1));
+        let extent = (max_x - min_x).max(max_y - min_y);
+        let scale = if extent * render.scale > available {
+            available / extent
+        } else {
+            render.scale
+        };
+        Raster {
+            min_x,
+            min_y,
+            scale,
+            padding,
+            width: ((max_x - min_x) * scale).ceil() as u32 + padding * 2 + 1,
+            height: ((max_y - min_y) * scale).ceil() as u32 + padding * 2 + 1,
+        }
+    }
+
+    /// Maps a coordinate to it's pixel, not including the padding.
+    fn to_pixel<T: Scalar>(&self, coord: Coord<T>) -> Coord<i64> {
+        Coord {
+            x: ((coord.x.to_f64() - self.min_x) * self.scale).round() as i64,
+            y: ((coord.y.to_f64() - self.min_y) * self.scale).round() as i64,
+        }
+    }
+}
+
+/// Draws a line segment onto an image.
+fn draw_segment<T: Scalar, G, P>(raster: &Raster, image: &mut G, segment: Segment<T>, color: P)
+where
+    G: GenericImage<Pixel = P>,
+    P: Pixel,
+{
+    let (a, b) = (raster.to_pixel(segment.a), raster.to_pixel(segment.b));
+    bresenham_line(a.x, a.y, b.x, b.y, image, raster.padding, color);
+}
+
+/// Draws a point onto an image, as a square that grows with the scale.
+fn draw_point<T: Scalar, G, P>(raster: &Raster, image: &mut G, point: Coord<T>, color: P)
+where
+    G: GenericImage<Pixel = P>,
+    P: Pixel,
+{
+    let point = raster.to_pixel(point);
+    let radius = (raster.scale / 2.0).max(0.0) as i64;
+    for x in (point.x - radius)..(point.x + radius + 1) {
+        for y in (point.y - radius)..(point.y + radius + 1) {
+            let (x, y) = (x + i64::from(raster.padding), y + i64::from(raster.padding));
+            if x >= 0 && y >= 0 && (x as u32) < image.width() && (y as u32) < image.height() {
+                image.put_pixel(x as u32, y as u32, color);
+            }
+        }
+    }
+}
+
+/// Converts the output to an svg vector image, viewing the bounds.
+///
+/// Each kind of shape is drawn in it's own layer, and given a class so it can be restyled. A frame
+/// of a trace is drawn as in `draw_image`, with the paths of the legs before it.
+fn output_to_svg<T: Scalar>(
+    output: &Output<T>,
+    frame: Option<&Frame<T>>,
+    bounds: (f64, f64, f64, f64),
+) -> Vec<u8> {
+    let (min_x, min_y, max_x, max_y) = bounds;
+    let size = (max_x - min_x).max(max_y - min_y).max(1.0);
+    let pad = size / 20.0;
+    let radius = size / 200.0;
+
+    let point = |coord: Coord<T>| format!("{},{}", coord.x.to_f64(), coord.y.to_f64());
+    let circle = |coord: Coord<T>, class: &str| {
+        format!(
+            "    <circle class=\"{}\" cx=\"{}\" cy=\"{}\" r=\"{}\"/>\n",
+            class,
+            coord.x.to_f64(),
+            coord.y.to_f64(),
+            radius
+        )
+    };
+    // Each ring is a separate subpath, so holes are left unfilled by the even-odd rule.
+    let outline = |polygon: &Polygon<T>| {
+        Some(&polygon.points)
+            .into_iter()
+            .chain(polygon.holes.iter().map(|hole| &hole.points))
+            .map(|ring| {
+                let points = ring.iter().map(|&coord| point(coord));
+                format!("M {} Z", points.collect::<Vec<_>>().join(" L "))
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        min_x - pad,
+        min_y - pad,
+        max_x - min_x + pad * 2.0,
+        max_y - min_y + pad * 2.0
+    ));
+    svg.push_str(concat!(
+        "  <style>\n",
+        "    line, path, polygon, polyline { fill: none; vector-effect: non-scaling-stroke; }\n",
+        "    .clearance { stroke: grey; stroke-dasharray: 4; }\n",
+        "    .polygon { stroke: black; fill: lightgrey; fill-rule: evenodd; }\n",
+        "    .hull { stroke: magenta; }\n",
+        "    .path { stroke: orange; stroke-width: 2; }\n",
+        "    .polypoint { fill: blue; }\n",
+        "    .route { fill: maroon; }\n",
+        "    .start { fill: lime; }\n",
+        "    .end { fill: red; }\n",
+    ));
+    let iteration = frame.and_then(|frame| frame.iteration);
+    if iteration.is_some() {
+        svg.push_str(concat!(
+            "    .intersected { stroke: orange; stroke-width: 2; }\n",
+            "    .traced { fill: magenta; }\n",
+        ));
+    }
+    svg.push_str("  </style>\n");
+
+    // Draw Clearance
+    if output.input.clearance > 0.0 {
+        svg.push_str("  <g class=\"clearances\">\n");
+        for polygon in &output.obstacles {
+            svg.push_str(&format!(
+                "    <path class=\"clearance\" d=\"{}\"/>\n",
+                outline(polygon)
+            ));
+        }
+        svg.push_str("  </g>\n");
+    }
+
+    // Draw Polygons
+    svg.push_str("  <g class=\"polygons\">\n");
+    for polygon in &output.input.polygons {
+        svg.push_str(&format!(
+            "    <path class=\"polygon\" d=\"{}\"/>\n",
+            outline(polygon)
+        ));
+    }
+    for polypoint in output
+        .input
+        .polygons
+        .iter()
+        .flat_map(|polygon| Some(polygon).into_iter().chain(polygon.holes.iter()))
+        .flat_map(|polygon| polygon.points.iter())
+    {
+        svg.push_str(&circle(*polypoint, "polypoint"));
+    }
+    svg.push_str("  </g>\n");
+
+    // Draw Hulls
+    svg.push_str("  <g class=\"hulls\">\n");
+    for hull in frame_hulls(output, frame) {
+        let points = hull.vertices().iter().map(|&coord| point(coord));
+        svg.push_str(&format!(
+            "    <polygon class=\"hull\" points=\"{}\"/>\n",
+            points.collect::<Vec<_>>().join(" ")
+        ));
+    }
+    svg.push_str("  </g>\n");
+
+    // Draw Paths
+    svg.push_str("  <g class=\"paths\">\n");
+    let paths = match frame {
+        Some(&Frame {
+            leg,
+            iteration: None,
+        }) => &output.paths[..(leg + 1).min(output.paths.len())],
+        Some(frame) => &output.paths[..frame.leg],
+        None => &output.paths[..],
+    };
+    for path in paths {
+        let points = path.points.iter().map(|&coord| point(coord));
+        svg.push_str(&format!(
+            "    <polyline class=\"path\" points=\"{}\"/>\n",
+            points.collect::<Vec<_>>().join(" ")
+        ));
+    }
+    svg.push_str("  </g>\n");
+
+    // Draw Trace
+    if let Some(iteration) = iteration {
+        svg.push_str("  <g class=\"trace\">\n");
+        for &polygon in &iteration.intersected {
+            svg.push_str(&format!(
+                "    <path class=\"intersected\" d=\"{}\"/>\n",
+                outline(&output.obstacles[polygon])
+            ));
+        }
+        for &polypoint in &iteration.polypoints {
+            svg.push_str(&circle(polypoint, "traced"));
+        }
+        svg.push_str("  </g>\n");
+    }
+
+    // Draw Route
+    svg.push_str("  <g class=\"route\">\n");
+    for &waypoint in &output.input.route {
+        svg.push_str(&circle(waypoint, "route"));
+    }
+    svg.push_str("  </g>\n");
+
+    // Draw Start and End
+    svg.push_str("  <g class=\"endpoints\">\n");
+    svg.push_str(&circle(output.input.start, "start"));
+    svg.push_str(&circle(output.input.end, "end"));
+    svg.push_str("  </g>\n");
+
+    svg.push_str("</svg>\n");
+    svg.into_bytes()
+}
+
+/// The hulls to draw, those of the legs before a frame and the hull of the frame, or every hull.
+fn frame_hulls<'a, T: Scalar>(
+    output: &'a Output<T>,
+    frame: Option<&Frame<'a, T>>,
+) -> Vec<&'a Hull<T>> {
+    match frame {
+        Some(frame) => {
+            let hull = match frame.iteration {
+                Some(iteration) => Some(&iteration.hull),
+                None => output.hulls.get(frame.leg),
+            };
+            output.hulls.iter().take(frame.leg).chain(hull).collect()
+        }
+        None => output.hulls.iter().collect(),
+    }
+}
+
+/// Computes the bounding box of everything in the output, as `(min_x, min_y, max_x, max_y)`.
+fn bounding_box<T: Scalar>(output: &Output<T>) -> (f64, f64, f64, f64) {
+    let start = output.input.start;
+    Some(output.input.end)
+        .into_iter()
+        .chain(output.input.route.iter().cloned())
+        .chain(
+            output
+                .input
+                .polygons
+                .iter()
+                .flat_map(|polygon| polygon.points.iter().cloned()),
+        )
+        .chain(
+            output
+                .obstacles
+                .iter()
+                .flat_map(|polygon| polygon.points.iter().cloned()),
+        )
+        .chain(
+            output
+                .hulls
+                .iter()
+                .flat_map(|hull| hull.vertices().iter().cloned()),
+        )
+        .chain(
+            output
+                .paths
+                .iter()
+                .flat_map(|path| path.points.iter().cloned()),
+        )
+        .fold(
+            (
+                start.x.to_f64(),
+                start.y.to_f64(),
+                start.x.to_f64(),
+                start.y.to_f64(),
+            ),
+            |(min_x, min_y, max_x, max_y), coord| {
+                let (x, y) = (coord.x.to_f64(), coord.y.to_f64());
+                (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+            },
         )
-        .unwrap();
-    buf
 }
 
 /// Standard Bresenham Line Algorithm
@@ -269,7 +1145,11 @@ where
     let mut e2;
 
     loop {
-        g.put_pixel(x0 as u32 + pad, y0 as u32 + pad, color);
+        // Pixels off the image are skipped, rather than wrapping around.
+        let (x, y) = (x0 + i64::from(pad), y0 + i64::from(pad));
+        if x >= 0 && y >= 0 && (x as u32) < g.width() && (y as u32) < g.height() {
+            g.put_pixel(x as u32, y as u32, color);
+        }
         if x0 == x1 && y0 == y1 {
             break;
         }
@@ -284,3 +1164,223 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example input with a single obstacle between the start and the end.
+    const SIMPLE: &str = include_str!("../examples/simple.toml");
+
+    /// Renders at the scale of the input, with a delay of a quarter of a second.
+    fn render() -> Render {
+        Render {
+            scale: 1.0,
+            padding: 10,
+            max_size: 8192,
+            delay: 250,
+        }
+    }
+
+    /// Runs the example input with a trace through the hull planner in an output mode.
+    fn run_simple(mode: &str) -> (Output<i64>, Vec<u8>, EncodedFrames) {
+        let options = Options {
+            planner: "hull",
+            algorithm: "quickhull",
+            mode,
+            trace: true,
+        };
+        let mut input = text_to_input::<i64>(SIMPLE, "simple.toml", "toml");
+        input.trace = true;
+        let output = input_to_output(&input, "hull", "quickhull", "simple.toml");
+        let (data, frames) = run::<i64>(SIMPLE, "simple.toml", "toml", &options, &render());
+        (output, data, frames)
+    }
+
+    /// The number of iterations traced in each leg of an output.
+    fn iterations(output: &Output<i64>) -> Vec<usize> {
+        output
+            .trace
+            .as_ref()
+            .unwrap()
+            .legs
+            .iter()
+            .map(|traced| traced.iterations.len())
+            .collect()
+    }
+
+    #[test]
+    fn gif_shows_each_iteration_and_leg_and_repeats_forever() {
+        let (output, data, frames) = run_simple("gif");
+        assert!(frames.is_empty());
+
+        let mut reader = gif::Decoder::new(&data[..]).read_info().unwrap();
+        let raster = Raster::fit(bounding_box(&output), &render());
+        let size = (reader.width(), reader.height());
+        assert_eq!(
+            (u32::from(size.0), u32::from(size.1)),
+            (raster.width, raster.height)
+        );
+        let mut delays = Vec::new();
+        while let Some(frame) = reader.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height), size);
+            delays.push(frame.delay);
+        }
+        // Every iteration of a leg, then the finished leg.
+        let expected = iterations(&output).iter().map(|count| count + 1).sum();
+        assert!(expected > 1);
+        assert_eq!(delays, vec![25; expected]);
+
+        // The application extension repeating forever, with a loop count of zero.
+        let repeat = b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00";
+        assert_eq!(
+            data.windows(repeat.len())
+                .filter(|window| window == repeat)
+                .count(),
+            1
+        );
+        assert_eq!(data.last(), Some(&0x3B));
+    }
+
+    #[test]
+    fn traced_png_and_svg_write_a_frame_per_iteration() {
+        for mode in &["png", "svg"] {
+            let (output, _, frames) = run_simple(mode);
+            let mut expected = Vec::new();
+            for (leg, count) in iterations(&output).into_iter().enumerate() {
+                expected.extend((0..count).map(|iteration| (leg, iteration)));
+            }
+            assert!(!expected.is_empty());
+            let written = frames
+                .iter()
+                .map(|&(leg, iteration, _)| (leg, iteration))
+                .collect::<Vec<_>>();
+            assert_eq!(written, expected, "{}", mode);
+            for (_, _, frame) in &frames {
+                match *mode {
+                    "png" => assert!(frame.starts_with(b"\x89PNG")),
+                    _ => assert!(frame.starts_with(b"<svg")),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn frame_files_are_named_before_the_extension() {
+        assert_eq!(frame_file("out.png", 0, 2), "out-0-2.png");
+        assert_eq!(frame_file("dir.d/out", 1, 0), "dir.d/out-1-0");
+        assert_eq!(frame_file("dir/.hidden", 0, 0), "dir/.hidden-0-0");
+    }
+
+    #[test]
+    fn scale_must_be_positive_and_finite() {
+        assert_eq!(parse_scale("2"), Ok(2.0));
+        assert_eq!(parse_scale("0.25"), Ok(0.25));
+        for scale in &["0", "-1", "-0.5", "NaN", "inf", "-inf", "", "large"] {
+            assert!(parse_scale(scale).is_err(), "{}", scale);
+        }
+    }
+
+    #[test]
+    fn raster_fits_the_bounds_with_padding() {
+        let bounds = (-10.0, -20.0, 30.0, 20.0);
+        let render = Render {
+            scale: 2.0,
+            padding: 5,
+            ..render()
+        };
+        let raster = Raster::fit(bounds, &render);
+        assert_eq!((raster.scale, raster.padding), (2.0, 5));
+        assert_eq!((raster.width, raster.height), (91, 91));
+        assert_eq!(
+            raster.to_pixel(Coord { x: -10, y: -20 }),
+            Coord { x: 0, y: 0 }
+        );
+        assert_eq!(
+            raster.to_pixel(Coord { x: 30, y: 20 }),
+            Coord { x: 80, y: 80 }
+        );
+
+        // The scale is reduced so the image fits in the maximum size.
+        let raster = Raster::fit(
+            bounds,
+            &Render {
+                max_size: 41,
+                ..render
+            },
+        );
+        assert_eq!(raster.scale, 0.75);
+        assert_eq!((raster.width, raster.height), (41, 41));
+
+        // The padding is limited to a quarter of the maximum size.
+        let raster = Raster::fit(
+            bounds,
+            &Render {
+                padding: 100,
+                max_size: 40,
+                ..render
+            },
+        );
+        assert_eq!(raster.padding, 10);
+        assert!(raster.width <= 40 && raster.height <= 40);
+
+        // A single point is drawn as a single pixel within the padding.
+        let raster = Raster::fit((3.0, 4.0, 3.0, 4.0), &render);
+        assert_eq!((raster.width, raster.height), (11, 11));
+    }
+
+    #[test]
+    fn lines_off_the_image_are_clipped() {
+        let mut image: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::new(4, 4);
+        let white = Rgb([255, 255, 255]);
+        bresenham_line(-5, -5, 5, 5, &mut image, 0, white);
+        bresenham_line(-3, 1, 8, 1, &mut image, 1, white);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            assert_eq!(*pixel == white, x == y || y == 2, "{} {}", x, y);
+        }
+    }
+
+    #[test]
+    fn svg_views_negative_coordinates() {
+        let input = text_to_input::<i64>(
+            concat!(
+                "[start]\nx = -30\ny = -10\n",
+                "[end]\nx = 50\ny = 20\n",
+                "[[polygon]]\n",
+                "point = [{ x = 0, y = -5 }, { x = 10, y = -5 }, { x = 10, y = 5 }, { x = 0, y = 5 }]\n",
+            ),
+            "negative.toml",
+            "toml",
+        );
+        let output = input_to_output(&input, "hull", "quickhull", "negative.toml");
+        let bounds = bounding_box(&output);
+        assert_eq!(bounds, (-30.0, -10.0, 50.0, 20.0));
+
+        // The padding is a twentieth of the larger side.
+        let svg = String::from_utf8(output_to_svg(&output, None, bounds)).unwrap();
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-34 -14 88 38\">"),
+            "{}",
+            svg
+        );
+    }
+
+    #[test]
+    fn input_format_is_chosen_by_option_then_extension_then_contents() {
+        let toml = "[start]\nx = 0\ny = 0\n";
+        let json = "{\"start\": {\"x\": 0, \"y\": 0}}";
+        let geojson = "{\"type\": \"FeatureCollection\", \"features\": []}";
+
+        assert_eq!(input_format(json, "in.toml", Some("geojson")), "geojson");
+        assert_eq!(input_format(json, "in.toml", None), "toml");
+        assert_eq!(input_format(toml, "in.json", None), "json");
+        assert_eq!(input_format(toml, "in.geojson", None), "geojson");
+
+        for file in &["-", "input", "in.txt"] {
+            assert_eq!(input_format(toml, file, None), "toml");
+            assert_eq!(input_format(json, file, None), "json");
+            assert_eq!(input_format(geojson, file, None), "geojson");
+            assert_eq!(input_format(&format!("\n  {}", json), file, None), "json");
+        }
+    }
+}
@@ -0,0 +1,422 @@
+//! Provides the HullAlgorithm trait, and the convex hull algorithms that implement it.
+//!
+//! Every algorithm produces the same hull for the same points: the corners of the hull, leaving
+//! out points that lie on an edge without being a corner. If every point is colinear the hull is
+//! the two extremes. Floating point coordinates are only treated alike up to rounding, so points
+//! that lie within rounding error of an edge may be kept by one algorithm and left out by another.
+//!
+//! Windings are described with the y axis pointing down, the sense in which `Orientation` and
+//! `Hull` name them.
+
+use error::Error;
+use shape::coord::Coord;
+use shape::hull::Hull;
+use shape::orientation::Orientation;
+use shape::scalar::Scalar;
+use shape::segment::Segment;
+use std::collections::{BTreeSet, HashMap};
+
+/// An algorithm for computing the convex hull of a set of points.
+///
+/// Algorithms are shared between threads when legs are processed in parallel.
+pub trait HullAlgorithm: Sync {
+    /// Calculates the convex hull of a set of points.
+    /// Fails when given fewer than two points.
+    fn hull<T: Scalar>(&self, points: &BTreeSet<Coord<T>>) -> Result<Hull<T>, Error>;
+}
+
+/// QuickHull, recursively dividing the points by the furthest point from a dividing line.
+/// O(n log n) expected, O(n²) in the worst case.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct QuickHull;
+
+impl HullAlgorithm for QuickHull {
+    fn hull<T: Scalar>(&self, points: &BTreeSet<Coord<T>>) -> Result<Hull<T>, Error> {
+        let mut hull = BTreeSet::new();
+        super::quick_hull(points, &mut hull)?;
+        Hull::from_segment_set(hull.into_iter().collect()).ok_or(Error::DegenerateInput {
+            points: points.len(),
+        })
+    }
+}
+
+/// Andrew's monotone chain, building the lower and upper hulls from the points sorted left to
+/// right. O(n log n).
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MonotoneChain;
+
+impl HullAlgorithm for MonotoneChain {
+    fn hull<T: Scalar>(&self, points: &BTreeSet<Coord<T>>) -> Result<Hull<T>, Error> {
+        check_degenerate(points)?;
+        // The set is already ordered left to right.
+        let points = points.iter().cloned().collect::<Vec<_>>();
+
+        let mut lower: Vec<Coord<T>> = Vec::new();
+        for &coord in &points {
+            while lower.len() > 1
+                && !turns_clockwise(lower[lower.len() - 2], lower[lower.len() - 1], coord)
+            {
+                lower.pop();
+            }
+            lower.push(coord);
+        }
+        let mut upper: Vec<Coord<T>> = Vec::new();
+        for &coord in points.iter().rev() {
+            while upper.len() > 1
+                && !turns_clockwise(upper[upper.len() - 2], upper[upper.len() - 1], coord)
+            {
+                upper.pop();
+            }
+            upper.push(coord);
+        }
+
+        // The last point of each chain is the first point of the other.
+        lower.pop();
+        upper.pop();
+        lower.extend(upper);
+        Ok(Hull::from_vertices(lower))
+    }
+}
+
+/// Graham scan, sorting the points by angle around the lowest point and walking them in order.
+/// O(n log n).
+#[derive(Debug, Copy, Clone, Default)]
+pub struct GrahamScan;
+
+impl HullAlgorithm for GrahamScan {
+    fn hull<T: Scalar>(&self, points: &BTreeSet<Coord<T>>) -> Result<Hull<T>, Error> {
+        check_degenerate(points)?;
+        let points = points.iter().cloned().collect::<Vec<_>>();
+        Ok(Hull::from_vertices(graham_ring(&points)))
+    }
+}
+
+/// Chan's algorithm, wrapping around hulls of small groups of points with a gift wrapping march,
+/// guessing ever larger group sizes until the march closes. O(n log h), for h hull vertices.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Chan;
+
+impl HullAlgorithm for Chan {
+    fn hull<T: Scalar>(&self, points: &BTreeSet<Coord<T>>) -> Result<Hull<T>, Error> {
+        check_degenerate(points)?;
+        let points = points.iter().cloned().collect::<Vec<_>>();
+
+        // Group sizes of 2^(2^t), squaring each round, capped at every point in one group.
+        let mut exponent = 2;
+        loop {
+            let group_size = 1usize
+                .checked_shl(exponent)
+                .map_or(points.len(), |size| size.min(points.len()));
+            // With every point in one group, the march would only walk that group's hull.
+            if group_size == points.len() {
+                return Ok(Hull::from_vertices(graham_ring(&points)));
+            }
+            if let Some(ring) = chan_march(&points, group_size) {
+                return Ok(Hull::from_vertices(ring));
+            }
+            exponent *= 2;
+        }
+    }
+}
+
+/// Marches around the hull of the points, in groups of `group_size`, taking at most `group_size`
+/// steps. Returns the vertices of the hull in clockwise order, or `None` if the march did not
+/// close in time.
+fn chan_march<T: Scalar>(points: &[Coord<T>], group_size: usize) -> Option<Vec<Coord<T>>> {
+    let groups = points
+        .chunks(group_size)
+        .map(graham_ring)
+        .collect::<Vec<_>>();
+    let positions = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(group, ring)| {
+            ring.iter()
+                .enumerate()
+                .map(move |(index, &coord)| (coord, (group, index)))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let start = *points.iter().min()?;
+    let mut ring = vec![start];
+
+    // The tangent from the current vertex to each group only moves clockwise around the group as
+    // the march does, so each group keeps a cursor that is walked forward.
+    let mut cursors = groups
+        .iter()
+        .map(|group| {
+            (0..group.len()).fold(0, |best, index| {
+                if is_better_wrap(start, group[best], group[index]) {
+                    index
+                } else {
+                    best
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for _ in 0..group_size {
+        let current = ring[ring.len() - 1];
+        let mut next: Option<Coord<T>> = None;
+        for (group, ring) in groups.iter().enumerate() {
+            let len = ring.len();
+            match positions.get(&current) {
+                // Within it's own group, the tangent is the vertex after it.
+                Some(&(own, index)) if own == group => cursors[group] = (index + 1) % len,
+                _ => {
+                    for _ in 0..len {
+                        let cursor = cursors[group];
+                        if is_better_wrap(current, ring[cursor], ring[(cursor + 1) % len]) {
+                            cursors[group] = (cursor + 1) % len;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+            let candidate = ring[cursors[group]];
+            if candidate == current {
+                continue;
+            }
+            next = match next {
+                Some(next) if !is_better_wrap(current, next, candidate) => Some(next),
+                _ => Some(candidate),
+            };
+        }
+
+        match next {
+            Some(next) if next == start => return Some(ring),
+            Some(next) => ring.push(next),
+            None => return Some(ring),
+        }
+    }
+    None
+}
+
+/// Computes the vertices of the hull of some distinct points in clockwise order, using a Graham
+/// scan.
+fn graham_ring<T: Scalar>(points: &[Coord<T>]) -> Vec<Coord<T>> {
+    let pivot = match points
+        .iter()
+        .min_by(|a, b| a.y.total_cmp(&b.y).then_with(|| a.x.total_cmp(&b.x)))
+    {
+        Some(&pivot) => pivot,
+        None => return Vec::new(),
+    };
+
+    // Every other point has a greater y than the pivot, or the same y and a greater x, so the
+    // angles around the pivot span less than a half turn and order totally.
+    let mut others = points
+        .iter()
+        .cloned()
+        .filter(|&coord| coord != pivot)
+        .collect::<Vec<_>>();
+    // Sort by an approximate angle first, then settle the order exactly with an insertion sort,
+    // which only has to move points past near ties, and cannot fail when floating point
+    // orientations are not transitive.
+    others.sort_by(|&a, &b| pseudo_angle(pivot, a).total_cmp(&pseudo_angle(pivot, b)));
+    for i in 1..others.len() {
+        let mut j = i;
+        while j > 0 && is_before_around(pivot, others[j], others[j - 1]) {
+            others.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+    // Of the points at the same angle, only the furthest can be a vertex.
+    let mut farthest: Vec<Coord<T>> = Vec::new();
+    for coord in others {
+        match farthest.last() {
+            Some(&last) if Orientation::from_coords(pivot, last, coord).is_colinear() => {
+                farthest.pop();
+            }
+            _ => {}
+        }
+        farthest.push(coord);
+    }
+
+    let mut ring = vec![pivot];
+    for coord in farthest {
+        while ring.len() > 1 && !turns_clockwise(ring[ring.len() - 2], ring[ring.len() - 1], coord)
+        {
+            ring.pop();
+        }
+        ring.push(coord);
+    }
+    ring
+}
+
+/// Approximates the angle of `coord` around `origin`, increasing clockwise from zero at the
+/// positive x axis to two at the negative x axis, for points with a y no less than the origin's.
+fn pseudo_angle<T: Scalar>(origin: Coord<T>, coord: Coord<T>) -> f64 {
+    let dx = coord.x.to_f64() - origin.x.to_f64();
+    let dy = coord.y.to_f64() - origin.y.to_f64();
+    1.0 - dx / (dx.abs() + dy)
+}
+
+/// Checks if `a` comes strictly before `b` clockwise around `origin`, with nearer points before
+/// further points at the same angle.
+fn is_before_around<T: Scalar>(origin: Coord<T>, a: Coord<T>, b: Coord<T>) -> bool {
+    match Orientation::from_coords(origin, a, b) {
+        Orientation::Colinear => is_further(origin, a, b),
+        _ => turns_clockwise(origin, a, b),
+    }
+}
+
+/// Checks if the march from `current` should wrap to `candidate` rather than `best`, because
+/// `candidate` lies counterclockwise of `best` around `current`, or on the line to it and further
+/// away.
+fn is_better_wrap<T: Scalar>(current: Coord<T>, best: Coord<T>, candidate: Coord<T>) -> bool {
+    if best == current {
+        return candidate != current;
+    }
+    match Orientation::from_coords(current, best, candidate) {
+        Orientation::Colinear => is_further(current, best, candidate),
+        _ => !turns_clockwise(current, best, candidate),
+    }
+}
+
+/// Checks if `c` makes a strict clockwise turn from `a` through `b`.
+fn turns_clockwise<T: Scalar>(a: Coord<T>, b: Coord<T>, c: Coord<T>) -> bool {
+    Orientation::from_coords(a, b, c) == Orientation::Clockwise
+}
+
+/// Checks if `b` lies beyond `a` on the ray from `origin` through `a`.
+fn is_further<T: Scalar>(origin: Coord<T>, a: Coord<T>, b: Coord<T>) -> bool {
+    a != b && Segment::from_coords(origin, b).contains_colinear_coord(a)
+}
+
+/// Fails when given fewer than two points.
+fn check_degenerate<T: Scalar>(points: &BTreeSet<Coord<T>>) -> Result<(), Error> {
+    if points.len() < 2 {
+        Err(Error::DegenerateInput {
+            points: points.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::Rng;
+
+    /// The hulls every algorithm gives for a set of points.
+    fn hulls<T: Scalar>(points: &BTreeSet<Coord<T>>) -> Vec<Result<Vec<Coord<T>>, Error>> {
+        vec![
+            QuickHull.hull(points),
+            MonotoneChain.hull(points),
+            GrahamScan.hull(points),
+            Chan.hull(points),
+        ]
+        .into_iter()
+        .map(|hull| hull.map(|hull| hull.points))
+        .collect()
+    }
+
+    /// Checks that every algorithm gives the same hull for a set of points, that the hull is
+    /// strictly convex and wound counterclockwise, and that no point lies outside it.
+    fn check_hull(points: &BTreeSet<Coord>) {
+        let hulls = hulls(points);
+        let hull = hulls[0].as_ref().unwrap();
+        for other in &hulls[1..] {
+            assert_eq!(other.as_ref().unwrap(), hull, "{:?}", points);
+        }
+
+        let len = hull.len();
+        if len > 2 {
+            for i in 0..len {
+                let (a, b) = (hull[i], hull[(i + 1) % len]);
+                let turn = Orientation::from_coords(a, b, hull[(i + 2) % len]);
+                assert_eq!(turn, Orientation::Counterclockwise, "{:?}", points);
+                for &point in points {
+                    let side = Orientation::from_coords(a, b, point);
+                    assert_ne!(side, Orientation::Clockwise, "{:?}", points);
+                }
+            }
+        }
+    }
+
+    /// Some random points within a square of a size.
+    fn random_points(rng: &mut Rng, count: usize, size: i64) -> BTreeSet<Coord> {
+        (0..count)
+            .map(|_| Coord {
+                x: rng.range(-size, size),
+                y: rng.range(-size, size),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn algorithms_agree_on_random_points() {
+        let mut rng = Rng::new(12);
+        for &count in &[2, 3, 5, 20, 100, 1000] {
+            for &size in &[3, 100, 1 << 40, i64::MAX / 2] {
+                for _ in 0..10 {
+                    check_hull(&random_points(&mut rng, count, size));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn algorithms_agree_on_colinear_points() {
+        let mut rng = Rng::new(13);
+        for &(dx, dy) in &[(1, 0), (0, 1), (1, 1), (2, -3)] {
+            for &count in &[2, 3, 50] {
+                let points = (0..count)
+                    .map(|_| {
+                        let step = rng.range(-1000, 1000);
+                        Coord {
+                            x: step * dx,
+                            y: step * dy,
+                        }
+                    })
+                    .collect::<BTreeSet<_>>();
+                if points.len() < 2 {
+                    continue;
+                }
+                check_hull(&points);
+                let extremes = vec![
+                    *points.iter().next().unwrap(),
+                    *points.iter().next_back().unwrap(),
+                ];
+                assert_eq!(hulls(&points)[0].as_ref().unwrap(), &extremes);
+            }
+        }
+    }
+
+    #[test]
+    fn algorithms_agree_on_points_along_edges() {
+        // A grid, with many points on every edge of its hull and many duplicated.
+        let mut rng = Rng::new(14);
+        for _ in 0..20 {
+            let points = (0..200)
+                .map(|_| Coord {
+                    x: rng.range(0, 6) * 3,
+                    y: rng.range(0, 4) * 5,
+                })
+                .collect::<BTreeSet<_>>();
+            check_hull(&points);
+        }
+    }
+
+    #[test]
+    fn algorithms_reject_a_single_point() {
+        let points = Some(Coord { x: 7, y: -2 })
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        for hull in hulls(&points) {
+            match hull {
+                Err(Error::DegenerateInput { points: 1 }) => {}
+                hull => panic!("expected a degenerate input, got {:?}", hull),
+            }
+        }
+        for hull in hulls::<i64>(&BTreeSet::new()) {
+            match hull {
+                Err(Error::DegenerateInput { points: 0 }) => {}
+                hull => panic!("expected a degenerate input, got {:?}", hull),
+            }
+        }
+    }
+}
@@ -1,112 +1,369 @@
 //! Provides the process function, as well as housing the internals for computing convex hulls.
 
+use error::Error;
 use io::input::Input;
-use io::output::Output;
+use io::output::{Iteration, Output, Snap, Trace, TracedLeg};
 use shape::orientation::Orientation;
+use shape::polygon::Polygon;
+use shape::containment::Containment;
 use shape::coord::Coord;
 use shape::segment::Segment;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use shape::hull::Hull;
-use std::hash::BuildHasher;
+use shape::index::ObstacleIndex;
+use shape::path::Path;
+use shape::scalar::Scalar;
+use shape::sweep;
+use std::cmp::Ordering;
 
-/// Processes the input into it's output by generating the convex hulls.
-pub fn process(input: &Input) -> Output {
-    let mut hulls = Vec::new();
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
-    let mut path = input.route.clone();
-    path.push(input.end);
-    path.insert(0, input.start);
-
-    let mut path = path.iter();
-
-    let mut origin;
-    let mut destination = path.next().unwrap(); // Guarunteed to have value
-    'generate_all_hulls: loop {
-        origin = destination;
-        let destination_o = path.next();
-        if destination_o.is_none() {
-            break 'generate_all_hulls;
-        }
-        destination = destination_o.unwrap();
-
-        let mut hull: HashSet<Segment> = HashSet::new();
-        let mut final_polypoints;
-        'generate_hull: loop {
-            let mut polypoints = Segment::from_coords(*origin, *destination)
-                .get_intersecting_polygon_coords(&input.polygons);
-
-            for hull_segment in &hull {
-                let union = polypoints
-                    .union(&hull_segment.get_intersecting_polygon_coords(&input.polygons))
-                    .cloned()
-                    .collect();
-            }
-            if polypoints == union {
-                final_polypoints = polypoints.clone();
-                final_polypoints.insert(*origin);
-                final_polypoints.insert(*destination);
-                break 'generate_hull;
-            }
+pub mod algorithm;
+pub mod order;
+pub mod visibility;
 
-            polypoints = union;
+use self::algorithm::{HullAlgorithm, QuickHull};
 
+/// Processes the input into it's output by generating the convex hulls with a hull algorithm.
+///
+/// A hull around an obstacle covers it's holes, so a leg inside a hole is only found while it's
+/// hull stays clear of the hole's edges, the visibility planner searches holes fully.
+pub fn process<T: Scalar, A: HullAlgorithm>(
+    input: &Input<T>,
+    algorithm: &A,
+) -> Result<Output<T>, Error> {
+    let (prepared, snaps) = prepare(input)?;
+    let obstacles = ObstacleIndex::new(&prepared.polygons);
 
-            polypoints.insert(*origin);
-            polypoints.insert(*destination);
+    let mut hulls = Vec::new();
+    let mut paths = Vec::new();
+    let mut traced = Vec::new();
+
+    let (path, order) = order_waypoints(&prepared, |origin, destination| {
+        match hull_leg(&obstacles, algorithm, origin, destination, None) {
+            Ok((_, Some(path))) => path.length,
+            _ => f64::INFINITY,
+        }
+    });
 
-            hull = hull.union(&calculate_hull(&polypoints)).cloned().collect();
+    let legs = path.windows(2).collect::<Vec<_>>();
+    let generated = map_in_order(&legs, |leg| {
+        let mut iterations = Vec::new();
+        let tracing = if prepared.trace {
+            Some(&mut iterations)
+        } else {
+            None
+        };
+        hull_leg(&obstacles, algorithm, leg[0], leg[1], tracing)
+            .map(|(hull, leg_path)| (hull, leg_path, iterations))
+    });
+    for (leg, generated) in generated.into_iter().enumerate() {
+        let (hull, leg_path, iterations) = generated?;
+        match leg_path {
+            Some(leg_path) => paths.push(leg_path),
+            None => return Err(Error::Unreachable { leg }),
         }
-        hull = calculate_hull(&final_polypoints);
-        hulls.push(Hull::from_segment_set(hull.into_iter().collect()));
+        hulls.push(hull);
+        traced.push(TracedLeg {
+            origin: legs[leg][0],
+            destination: legs[leg][1],
+            iterations,
+        });
     }
 
-    Output {
+    Ok(Output {
         input: input.clone(),
-        hulls: hulls,
-    }
+        obstacles: prepared.polygons,
+        hulls,
+        paths,
+        snaps,
+        order,
+        trace: if prepared.trace {
+            Some(Trace { legs: traced })
+        } else {
+            None
+        },
+    })
+}
+
+/// Maps each item to it's result, keeping their order, across threads if the `parallel` feature
+/// is enabled. Used for the legs of a route and the rows of the cost matrix between waypoints.
+///
+/// Every item is mapped even if an earlier one fails, so the first failure in order is the same
+/// either way.
+#[cfg(feature = "parallel")]
+fn map_in_order<I: Sync, R: Send, F: Fn(&I) -> R + Send + Sync>(items: &[I], map: F) -> Vec<R> {
+    items.par_iter().map(map).collect()
 }
 
-/// Calculates the points that lie in the hull of a set of points.
-pub fn calculate_hull<S: BuildHasher>(polypoints: &HashSet<Coord, S>) -> HashSet<Segment> {
-    let mut hull = HashSet::new();
+/// Maps each item to it's result, keeping their order, across threads if the `parallel` feature
+/// is enabled.
+#[cfg(not(feature = "parallel"))]
+fn map_in_order<I: Sync, R: Send, F: Fn(&I) -> R + Send + Sync>(items: &[I], map: F) -> Vec<R> {
+    items.iter().map(map).collect()
+}
 
-    if !quick_hull(polypoints, &mut hull) {
-        panic!();
+/// Generates the hull around the obstacles in the way of a leg, and the shortest path along it,
+/// which is `None` if the path cannot follow the hull. Each hull built on the way is pushed onto
+/// the iterations if given.
+///
+/// A leg between coincident waypoints has a hull of that single point, and a path of no length.
+fn hull_leg<T: Scalar, A: HullAlgorithm>(
+    obstacles: &ObstacleIndex<T>,
+    algorithm: &A,
+    origin: Coord<T>,
+    destination: Coord<T>,
+    mut iterations: Option<&mut Vec<Iteration<T>>>,
+) -> Result<(Hull<T>, Option<Path<T>>), Error> {
+    if origin == destination {
+        let hull = Hull::from_vertices(vec![origin]);
+        let path = Path::from_hull(&hull, origin, destination);
+        return Ok((hull, path));
     }
 
-    hull
+    let mut polypoints =
+        obstacles.intersecting_polygon_coords(&Segment::from_coords(origin, destination));
+    polypoints.insert(origin);
+    polypoints.insert(destination);
+
+    let mut hull;
+    'generate_hull: loop {
+        hull = algorithm.hull(&polypoints)?;
+
+        let mut union = polypoints.clone();
+        let mut intersected = Vec::new();
+        let crossed = hull
+            .segments()
+            .iter()
+            .flat_map(|hull_segment| obstacles.intersecting_polygons(hull_segment))
+            .collect::<BTreeSet<_>>();
+        for polygon in crossed {
+            let points = &obstacles.polygons()[polygon].points;
+            if points.iter().any(|point| !polypoints.contains(point)) {
+                union.extend(points.iter().cloned());
+                intersected.push(polygon);
+            }
+        }
+        if let Some(ref mut iterations) = iterations {
+            iterations.push(Iteration {
+                intersected: intersected.clone(),
+                polypoints: polypoints.iter().cloned().collect(),
+                hull: hull.clone(),
+            });
+        }
+        if intersected.is_empty() {
+            break 'generate_hull;
+        }
+
+        polypoints = union;
+    }
+    let path = Path::from_hull(&hull, origin, destination);
+    Ok((hull, path))
 }
 
-/// Calculates the quick hull of a set of points, outputting it into a buffer.
-/// Returns true when computation is successful.
-pub fn quick_hull<S1: BuildHasher, S2: BuildHasher>(
-    input: &HashSet<Coord, S1>,
-    hull: &mut HashSet<Segment, S2>,
-) -> bool {
-    if input.len() < 2 {
-        return false;
+/// Lists the waypoints in the order they are visited, from the start, through the route, to the
+/// end, along with the order the route points are visited in.
+///
+/// An unordered route is put in the order with the least total cost of it's legs, otherwise the
+/// route keeps it's order and the returned order is empty.
+fn order_waypoints<T: Scalar, F>(input: &Input<T>, leg_cost: F) -> (Vec<Coord<T>>, Vec<usize>)
+where
+    F: Fn(Coord<T>, Coord<T>) -> f64 + Send + Sync,
+{
+    let mut waypoints = Some(input.start)
+        .into_iter()
+        .chain(input.route.iter().cloned())
+        .chain(Some(input.end))
+        .collect::<Vec<_>>();
+    if !input.unordered_route {
+        return (waypoints, Vec::new());
+    }
+
+    let costs = map_in_order(&waypoints, |&origin| {
+        waypoints
+            .iter()
+            .map(|&destination| {
+                if origin == destination {
+                    0.0
+                } else {
+                    leg_cost(origin, destination)
+                }
+            })
+            .collect()
+    });
+    let order = order::visiting_order(&costs);
+
+    let end = waypoints.pop().unwrap(); // Guarunteed to have value
+    waypoints.truncate(1);
+    waypoints.extend(order.iter().map(|&point| input.route[point]));
+    waypoints.push(end);
+    (waypoints, order)
+}
+
+/// Prepares an input for processing, normalizing it's polygons, checking that they are well
+/// formed, inflating them by the clearance, merging those that overlap, and checking that no
+/// waypoint lies inside one. A negative clearance is rejected up front.
+///
+/// Errors about a merged polygon give the index of the first input polygon merged into it.
+///
+/// If the input allows it, waypoints inside a polygon are snapped to the nearest point on it's
+/// boundary instead, and the snaps are returned alongside the prepared input.
+fn prepare<T: Scalar>(input: &Input<T>) -> Result<(Input<T>, Vec<Snap<T>>), Error> {
+    if input.clearance < 0.0 || input.clearance.is_nan() {
+        return Err(Error::InvalidClearance);
+    }
+
+    let mut polygons = input.polygons.clone();
+    for (polygon_index, polygon) in polygons.iter_mut().enumerate() {
+        polygon.normalize();
+        let report = polygon.validate();
+        if report.too_few_points {
+            return Err(Error::InvalidPolygon {
+                polygon: polygon_index,
+            });
+        }
+        if let Some(&edges) = report.self_intersections.first() {
+            return Err(Error::SelfIntersectingPolygon {
+                polygon: polygon_index,
+                edges,
+            });
+        }
+        if let Some(&hole) = report.invalid_holes.first() {
+            return Err(Error::InvalidHole {
+                polygon: polygon_index,
+                hole,
+            });
+        }
+        *polygon = polygon.offset(input.clearance, input.join);
     }
+    let (indices, polygons): (Vec<_>, Vec<_>) = merge_overlapping(polygons).into_iter().unzip();
 
-    let (leftest, rightest) = input.iter().fold(
-        (None, None),
-        |(mut leftest, mut rightest), &item| {
-            if leftest.is_none() {
-                leftest = Some(item);
+    let mut waypoints = Some(input.start)
+        .into_iter()
+        .chain(input.route.iter().cloned())
+        .chain(Some(input.end))
+        .collect::<Vec<_>>();
+    let mut snaps = Vec::new();
+    if input.snap {
+        for (waypoint_index, waypoint) in waypoints.iter_mut().enumerate() {
+            for (polygon_index, polygon) in polygons.iter().enumerate() {
+                if polygon.contains(*waypoint) != Containment::Inside {
+                    continue;
+                }
+                if let Some(point) = polygon.nearest_boundary_point(*waypoint) {
+                    *waypoint = point;
+                    snaps.push(Snap {
+                        waypoint: waypoint_index,
+                        polygon: indices[polygon_index],
+                        point,
+                    });
+                }
             }
-            if rightest.is_none() {
-                rightest = Some(item);
+        }
+    }
+
+    // Snapping out of one polygon can land inside another close by.
+    for (waypoint_index, &waypoint) in waypoints.iter().enumerate() {
+        for (polygon_index, polygon) in polygons.iter().enumerate() {
+            if polygon.contains(waypoint) == Containment::Inside {
+                return Err(Error::WaypointInsideObstacle {
+                    waypoint: waypoint_index,
+                    polygon: indices[polygon_index],
+                });
             }
-            if item.x < leftest.unwrap().x {
-                leftest = Some(item);
+        }
+    }
+
+    let end = waypoints.pop().unwrap(); // Guarunteed to have value
+    let start = waypoints.remove(0);
+    Ok((
+        Input {
+            start,
+            end,
+            route: waypoints,
+            polygons,
+            ..input.clone()
+        },
+        snaps,
+    ))
+}
+
+/// Merges polygons that overlap or share part of an edge into single outlines, so the free space
+/// between them is not mistaken for a way through. Each outline is paired with the index of the
+/// first polygon merged into it, and they are ordered by it.
+fn merge_overlapping<T: Scalar>(polygons: Vec<Polygon<T>>) -> Vec<(usize, Polygon<T>)> {
+    // Outlines can only merge if some of the polygons merged into them meet or nest.
+    let overlapping = sweep::overlapping_polygons(&polygons)
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let overlaps = |first: &[usize], second: &[usize]| {
+        first.iter().any(|&a| {
+            second
+                .iter()
+                .any(|&b| overlapping.contains(&(a.min(b), a.max(b))))
+        })
+    };
+
+    let mut merged: Vec<(Vec<usize>, Polygon<T>)> = Vec::new();
+    for (index, polygon) in polygons.into_iter().enumerate() {
+        let mut current = (vec![index], polygon);
+        // A merged outline can reach polygons that neither part did, so repeat until none do.
+        loop {
+            let mut found = None;
+            for (position, (members, other)) in merged.iter().enumerate() {
+                if !overlaps(&current.0, members) {
+                    continue;
+                }
+                let mut union = current.1.union(other);
+                if union.len() == 1 {
+                    found = Some((position, union.remove(0)));
+                    break;
+                }
             }
-            if item.x > rightest.unwrap().x {
-                rightest = Some(item);
+            match found {
+                Some((position, union)) => {
+                    let (members, _) = merged.remove(position);
+                    current.0.extend(members);
+                    current.1 = union;
+                }
+                None => break,
             }
-            (leftest, rightest)
-        },
-    );
-    let leftest = leftest.unwrap();
-    let rightest = rightest.unwrap();
+        }
+        merged.push(current);
+    }
+    let mut merged = merged
+        .into_iter()
+        .map(|(members, polygon)| {
+            let first = *members.iter().min().unwrap(); // Guarunteed to have value
+            (first, polygon)
+        })
+        .collect::<Vec<_>>();
+    merged.sort_by_key(|&(index, _)| index);
+    merged
+}
+
+/// Calculates the hull of a set of points with QuickHull.
+pub fn calculate_hull<T: Scalar>(polypoints: &BTreeSet<Coord<T>>) -> Result<Hull<T>, Error> {
+    QuickHull.hull(polypoints)
+}
+
+/// Calculates the quick hull of a set of points, outputting it into a buffer.
+/// Fails when given fewer than two points.
+pub fn quick_hull<T: Scalar>(
+    input: &BTreeSet<Coord<T>>,
+    hull: &mut BTreeSet<Segment<T>>,
+) -> Result<(), Error> {
+    if input.len() < 2 {
+        return Err(Error::DegenerateInput {
+            points: input.len(),
+        });
+    }
+
+    // The set is ordered by x then y, so the extremes are distinct even when the points lie on a
+    // vertical line.
+    let leftest = *input.iter().next().unwrap();
+    let rightest = *input.iter().next_back().unwrap();
 
     quick_hull_recurse(input, leftest, rightest, Orientation::Clockwise, hull);
     quick_hull_recurse(
@@ -117,44 +374,306 @@ pub fn quick_hull<S1: BuildHasher, S2: BuildHasher>(
         hull,
     );
 
-    true
+    Ok(())
 }
 
 /// The recursive call component of `quick_hull`.
-fn quick_hull_recurse<S1: BuildHasher, S2: BuildHasher>(
-    input: &HashSet<Coord, S1>,
-    p1: Coord,
-    p2: Coord,
+fn quick_hull_recurse<T: Scalar>(
+    input: &BTreeSet<Coord<T>>,
+    p1: Coord<T>,
+    p2: Coord<T>,
     orientation: Orientation,
-    hull: &mut HashSet<Segment, S2>,
+    hull: &mut BTreeSet<Segment<T>>,
 ) {
-    let mut divider: Option<Coord> = None;
-    let mut max_dist = 0;
+    let mut divider: Option<Coord<T>> = None;
+    let line = Segment::from_coords(p1, p2);
 
     for &coord in input.iter() {
-        let dist = Segment::from_coords(p1, p2).coord_distance(coord);
-        if Orientation::from_coords(p1, p2, coord) == orientation && dist > max_dist {
-            divider = Some(coord);
-            max_dist = dist;
+        if Orientation::from_coords(p1, p2, coord) == orientation {
+            // Of equally distant points, the one furthest along the line is a corner of the hull,
+            // while the others may lie on it's edge.
+            let further = match divider {
+                Some(divider) => {
+                    line.compare_coord_distance(coord, divider)
+                        .then_with(|| compare_projection(p1, p2, coord, divider))
+                        == Ordering::Greater
+                }
+                None => true,
+            };
+            if further {
+                divider = Some(coord);
+            }
         }
     }
 
+    // The outside of each new line is away from the other end of the old line. It follows from
+    // the orientation of the divider, rather than being recomputed, so nearly colinear floating
+    // point coordinates cannot make it colinear.
     if let Some(divider) = divider {
-        quick_hull_recurse(
-            input,
-            divider,
-            p1,
-            Orientation::from_coords(divider, p1, p2).invert(),
-            hull,
-        );
-        quick_hull_recurse(
-            input,
-            divider,
-            p2,
-            Orientation::from_coords(divider, p2, p1).invert(),
-            hull,
-        );
+        quick_hull_recurse(input, divider, p1, orientation.invert(), hull);
+        quick_hull_recurse(input, divider, p2, orientation, hull);
     } else {
         hull.insert(Segment::from_coords(p1, p2));
     }
 }
+
+/// Compares how far two points project along the direction from `p1` to `p2`.
+fn compare_projection<T: Scalar>(p1: Coord<T>, p2: Coord<T>, a: Coord<T>, b: Coord<T>) -> Ordering {
+    // The dot product of `a - b` with `p2 - p1`.
+    T::determinant_sign([b.x, a.x], [p1.x, p2.x], [a.y, b.y], [p1.y, p2.y])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use process::algorithm::{Chan, GrahamScan, MonotoneChain};
+    use shape::offset::Join;
+
+    /// A coordinate.
+    fn coord(x: i64, y: i64) -> Coord {
+        Coord { x, y }
+    }
+
+    /// An input with a square obstacle between the start and end.
+    fn input(route: Vec<Coord>, unordered_route: bool) -> Input {
+        Input {
+            start: coord(0, 0),
+            end: coord(20, 0),
+            route,
+            unordered_route,
+            polygons: vec![Polygon::from_points(vec![
+                coord(8, -4),
+                coord(12, -4),
+                coord(12, 4),
+                coord(8, 4),
+            ])],
+            clearance: 0.0,
+            join: Join::default(),
+            snap: false,
+            trace: false,
+        }
+    }
+
+    /// Runs an input through both planners.
+    fn outputs(input: &Input) -> Vec<Output> {
+        vec![
+            process(input, &QuickHull).unwrap(),
+            visibility::process(input).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn coincident_waypoints_give_a_zero_length_leg() {
+        for output in outputs(&input(vec![coord(0, 0), coord(20, 0)], false)) {
+            assert_eq!(output.paths.len(), 3);
+            for &leg in &[0, 2] {
+                assert_eq!(output.paths[leg].length, 0.0);
+            }
+            assert_eq!(output.paths[0].points, vec![coord(0, 0)]);
+            assert_eq!(output.paths[2].points, vec![coord(20, 0)]);
+            assert!(output.paths[1].length > 20.0);
+        }
+    }
+
+    #[test]
+    fn negative_clearance_is_rejected() {
+        for &clearance in &[-1.0, f64::NAN] {
+            let input = Input {
+                clearance,
+                ..input(Vec::new(), false)
+            };
+            assert_eq!(
+                process(&input, &QuickHull).err(),
+                Some(Error::InvalidClearance)
+            );
+            assert_eq!(
+                visibility::process(&input).err(),
+                Some(Error::InvalidClearance)
+            );
+        }
+    }
+
+    #[test]
+    fn unordered_route_may_revisit_the_start() {
+        for output in outputs(&input(vec![coord(20, 0), coord(0, 0)], true)) {
+            assert_eq!(output.order, vec![1, 0]);
+            assert_eq!(output.paths.len(), 3);
+            assert_eq!(output.paths[0].points, vec![coord(0, 0)]);
+            assert_eq!(output.paths[2].points, vec![coord(20, 0)]);
+        }
+    }
+
+    #[test]
+    fn merged_obstacles_close_the_gap_between_them() {
+        // Two squares stacked across the straight line, the upper one `gap` above the lower.
+        let stacked = |gap| Input {
+            polygons: vec![
+                Polygon::from_points(vec![coord(8, -4), coord(12, -4), coord(12, 0), coord(8, 0)]),
+                Polygon::from_points(vec![
+                    coord(8, gap),
+                    coord(12, gap),
+                    coord(12, gap + 4),
+                    coord(8, gap + 4),
+                ]),
+            ],
+            ..input(Vec::new(), false)
+        };
+
+        // Apart, the straight line slides between them along the lower square.
+        for output in outputs(&stacked(1)) {
+            assert_eq!(output.obstacles.len(), 2);
+            assert_eq!(output.paths[0].points, vec![coord(0, 0), coord(20, 0)]);
+        }
+        // Sharing an edge, they merge into one obstacle, and the way between them is closed.
+        for output in outputs(&stacked(0)) {
+            assert_eq!(output.obstacles.len(), 1);
+            assert_eq!(output.obstacles[0].points.len(), 4);
+            assert!(output.paths[0].length > 20.0);
+            assert!(!output.paths[0].points.contains(&coord(12, 0)));
+        }
+    }
+
+    #[test]
+    fn trace_grows_the_hull_around_a_concave_obstacle() {
+        // A bracket opening towards the start, which the straight line enters, and a square the
+        // first hull cuts across on it's way around the bracket.
+        let input = Input {
+            end: coord(40, 0),
+            polygons: vec![
+                Polygon::from_points(vec![
+                    coord(10, -10),
+                    coord(20, -10),
+                    coord(20, 10),
+                    coord(10, 10),
+                    coord(10, 6),
+                    coord(16, 6),
+                    coord(16, -6),
+                    coord(10, -6),
+                ]),
+                Polygon::from_points(vec![coord(4, 3), coord(6, 3), coord(6, 5), coord(4, 5)]),
+            ],
+            trace: true,
+            ..input(Vec::new(), false)
+        };
+        let outputs = vec![
+            process(&input, &QuickHull).unwrap(),
+            process(&input, &MonotoneChain).unwrap(),
+            process(&input, &GrahamScan).unwrap(),
+            process(&input, &Chan).unwrap(),
+        ];
+        for output in outputs {
+            let trace = output.trace.unwrap();
+            assert_eq!(trace.legs.len(), 1);
+            let iterations = &trace.legs[0].iterations;
+
+            // The first hull only knows the bracket, and crosses the square, which the second
+            // takes in.
+            let intersected = iterations
+                .iter()
+                .map(|iteration| iteration.intersected.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(intersected, vec![vec![1], vec![]]);
+            assert_eq!(iterations[0].polypoints.len(), 10);
+            assert_eq!(iterations[1].polypoints.len(), 14);
+            assert!(!iterations[0].hull.vertices().contains(&coord(4, 5)));
+            assert!(iterations[1].hull.vertices().contains(&coord(4, 5)));
+
+            // The last hull is the one kept, and the path runs along it.
+            let last = &iterations[iterations.len() - 1].hull;
+            assert_eq!(last.vertices(), output.hulls[0].vertices());
+            let path = Path::from_hull(last, input.start, input.end).unwrap();
+            assert_eq!(output.paths[0].points, path.points);
+            assert_eq!(output.paths[0].length, path.length);
+        }
+    }
+
+    /// Orders the waypoints of an input as `order_waypoints` does, computing the costs on this
+    /// thread with a plain map.
+    #[cfg(feature = "parallel")]
+    fn sequential_order<F: Fn(Coord, Coord) -> f64>(
+        input: &Input,
+        leg_cost: F,
+    ) -> (Vec<Coord>, Vec<usize>) {
+        let mut waypoints = Some(input.start)
+            .into_iter()
+            .chain(input.route.iter().cloned())
+            .chain(Some(input.end))
+            .collect::<Vec<_>>();
+        if !input.unordered_route {
+            return (waypoints, Vec::new());
+        }
+        let costs = waypoints
+            .iter()
+            .map(|&origin| {
+                waypoints
+                    .iter()
+                    .map(|&destination| {
+                        if origin == destination {
+                            0.0
+                        } else {
+                            leg_cost(origin, destination)
+                        }
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let order = order::visiting_order(&costs);
+        let end = waypoints.pop().unwrap();
+        waypoints.truncate(1);
+        waypoints.extend(order.iter().map(|&point| input.route[point]));
+        waypoints.push(end);
+        (waypoints, order)
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_legs_match_a_sequential_map() {
+        let obstacle = |x: i64| {
+            Polygon::from_points(vec![
+                coord(x - 3, -3),
+                coord(x + 3, -3),
+                coord(x + 3, 3),
+                coord(x - 3, 3),
+            ])
+        };
+        for &unordered_route in &[false, true] {
+            let input = Input {
+                end: coord(100, 0),
+                polygons: [10, 30, 50, 70, 90].iter().map(|&x| obstacle(x)).collect(),
+                ..input(
+                    vec![coord(60, 6), coord(20, -6), coord(80, -6), coord(40, 6)],
+                    unordered_route,
+                )
+            };
+            let (prepared, _) = prepare(&input).unwrap();
+
+            let obstacles = ObstacleIndex::new(&prepared.polygons);
+            let hull_leg = |origin, destination| {
+                hull_leg(&obstacles, &QuickHull, origin, destination, None).unwrap()
+            };
+            let (waypoints, order) = sequential_order(&prepared, |origin, destination| {
+                hull_leg(origin, destination).1.unwrap().length
+            });
+            let output = process(&input, &QuickHull).unwrap();
+            assert_eq!(output.order, order);
+            assert_eq!(output.paths.len(), waypoints.len() - 1);
+            for (leg, pair) in waypoints.windows(2).enumerate() {
+                let (hull, path) = hull_leg(pair[0], pair[1]);
+                assert_eq!(output.hulls[leg].vertices(), hull.vertices());
+                assert_eq!(output.paths[leg].points, path.unwrap().points);
+            }
+
+            let graph = visibility::VisibilityGraph::from_input(&prepared);
+            let (waypoints, order) = sequential_order(&prepared, |origin, destination| {
+                graph.shortest_path(origin, destination).unwrap().length
+            });
+            let output = visibility::process(&input).unwrap();
+            assert_eq!(output.order, order);
+            assert_eq!(output.paths.len(), waypoints.len() - 1);
+            for (leg, pair) in waypoints.windows(2).enumerate() {
+                let path = graph.shortest_path(pair[0], pair[1]).unwrap();
+                assert_eq!(output.paths[leg].points, path.points);
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! Provides the choice of order to visit an unordered route in, as a travelling salesman problem
+//! between a fixed start and end.
+
+use std::cmp::Ordering;
+
+/// The most route points that are ordered exactly, beyond which heuristics are used.
+pub const EXACT_LIMIT: usize = 12;
+
+/// The least decrease in cost that counts as an improvement, so rounding cannot cycle.
+const IMPROVEMENT: f64 = 1e-9;
+
+/// Chooses the order to visit the route points in, minimising the total cost from start to end.
+///
+/// The costs are between the waypoints, the start first, then the route points, and finally the
+/// end, with infinite costs where no path exists. Returns the indices of the route points in the
+/// order to visit them. The order is exact for up to `EXACT_LIMIT` route points, and otherwise
+/// built by nearest neighbour and improved with 2-opt and Or-opt moves.
+pub fn visiting_order(costs: &[Vec<f64>]) -> Vec<usize> {
+    let count = costs.len().saturating_sub(2);
+    let tour = if count <= EXACT_LIMIT {
+        held_karp(costs, count)
+    } else {
+        heuristic(costs, count)
+    };
+    tour.into_iter().map(|node| node - 1).collect()
+}
+
+/// Builds an order by nearest neighbour, improving it with 2-opt and Or-opt moves until neither
+/// finds a cheaper one.
+fn heuristic(costs: &[Vec<f64>], count: usize) -> Vec<usize> {
+    let mut tour = nearest_neighbour(costs, count);
+    while two_opt(costs, &mut tour) || or_opt(costs, &mut tour) {}
+    tour
+}
+
+/// Finds the cheapest order by dynamic programming over the subsets of route points.
+fn held_karp(costs: &[Vec<f64>], count: usize) -> Vec<usize> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let end = count + 1;
+    let full = (1 << count) - 1;
+
+    // The cheapest cost to visit a subset of the route points, finishing at one of them, and the
+    // point visited before it.
+    let mut best = vec![vec![f64::INFINITY; count]; full + 1];
+    let mut previous = vec![vec![None; count]; full + 1];
+    for last in 0..count {
+        best[1 << last][last] = costs[0][last + 1];
+    }
+    for subset in 1..(full + 1) {
+        for last in (0..count).filter(|&last| subset & (1 << last) != 0) {
+            let cost = best[subset][last];
+            if cost == f64::INFINITY {
+                continue;
+            }
+            for next in (0..count).filter(|&next| subset & (1 << next) == 0) {
+                let extended = subset | (1 << next);
+                let next_cost = cost + costs[last + 1][next + 1];
+                if next_cost < best[extended][next] {
+                    best[extended][next] = next_cost;
+                    previous[extended][next] = Some(last);
+                }
+            }
+        }
+    }
+
+    let total = |last: usize| best[full][last] + costs[last + 1][end];
+    let mut last = (0..count)
+        .min_by(|&a, &b| total(a).partial_cmp(&total(b)).unwrap_or(Ordering::Equal))
+        .unwrap(); // Guarunteed to have value
+    if total(last) == f64::INFINITY {
+        // No order reaches the end, so any will do.
+        return (1..(count + 1)).collect();
+    }
+    let mut subset = full;
+    let mut tour = vec![last + 1];
+    while let Some(before) = previous[subset][last] {
+        subset &= !(1 << last);
+        last = before;
+        tour.push(last + 1);
+    }
+    tour.reverse();
+    tour
+}
+
+/// Builds an order by always visiting the cheapest unvisited point next.
+fn nearest_neighbour(costs: &[Vec<f64>], count: usize) -> Vec<usize> {
+    let mut unvisited = (1..(count + 1)).collect::<Vec<_>>();
+    let mut tour = Vec::with_capacity(count);
+    let mut current = 0;
+    while !unvisited.is_empty() {
+        let nearest = (0..unvisited.len())
+            .min_by(|&a, &b| {
+                costs[current][unvisited[a]]
+                    .partial_cmp(&costs[current][unvisited[b]])
+                    .unwrap_or(Ordering::Equal)
+            })
+            .unwrap(); // Guarunteed to have value
+        current = unvisited.remove(nearest);
+        tour.push(current);
+    }
+    tour
+}
+
+/// Reverses a stretch of the order if that makes it cheaper. Returns true if it did.
+///
+/// Only the costs that change are compared, those of the two edges around the stretch and of the
+/// stretch itself in each direction, which are summed as the stretch grows, as the costs need not
+/// be the same both ways.
+fn two_opt(costs: &[Vec<f64>], tour: &mut [usize]) -> bool {
+    for first in 0..tour.len() {
+        let before = waypoint(costs, tour, first);
+        let (mut forward, mut backward) = (0.0, 0.0);
+        for last in (first + 1)..tour.len() {
+            forward += costs[tour[last - 1]][tour[last]];
+            backward += costs[tour[last]][tour[last - 1]];
+            let after = waypoint(costs, tour, last + 2);
+            let current = costs[before][tour[first]] + forward + costs[tour[last]][after];
+            let reversed = costs[before][tour[last]] + backward + costs[tour[first]][after];
+            if reversed < current - IMPROVEMENT {
+                tour[first..(last + 1)].reverse();
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Moves a run of up to three points elsewhere in the order if that makes it cheaper. Returns
+/// true if it did.
+///
+/// Only the costs of the three edges that change are compared, those either side of the run and
+/// the one it is moved into.
+fn or_opt(costs: &[Vec<f64>], tour: &mut Vec<usize>) -> bool {
+    for length in 1..4 {
+        for start in 0..(tour.len().saturating_sub(length - 1)) {
+            let (first, last) = (tour[start], tour[start + length - 1]);
+            let before = waypoint(costs, tour, start);
+            let after = waypoint(costs, tour, start + length + 1);
+            // The waypoints either side of each gap of the order without the run, skipping the
+            // gap it was taken from.
+            let skipping = |position: usize| {
+                let index = if position > start {
+                    position + length
+                } else {
+                    position
+                };
+                waypoint(costs, tour, index)
+            };
+            for position in (0..(tour.len() - length + 1)).filter(|&position| position != start) {
+                let (from, to) = (skipping(position), skipping(position + 1));
+                let current = costs[before][first] + costs[last][after] + costs[from][to];
+                let moved = costs[before][after] + costs[from][first] + costs[last][to];
+                if moved < current - IMPROVEMENT {
+                    let run = tour.drain(start..(start + length)).collect::<Vec<_>>();
+                    tour.splice(position..position, run);
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// The waypoint at an index of the order with the start before it and the end after it.
+fn waypoint(costs: &[Vec<f64>], tour: &[usize], index: usize) -> usize {
+    match index {
+        0 => 0,
+        index if index > tour.len() => costs.len() - 1,
+        index => tour[index - 1],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::Rng;
+
+    /// The total cost of visiting the route points in order, from the start to the end.
+    fn tour_cost(costs: &[Vec<f64>], tour: &[usize]) -> f64 {
+        let end = costs.len() - 1;
+        Some(0)
+            .into_iter()
+            .chain(tour.iter().cloned())
+            .zip(tour.iter().cloned().chain(Some(end)))
+            .map(|(from, to)| costs[from][to])
+            .sum()
+    }
+
+    /// The straight line distances between some points.
+    fn distances(points: &[(f64, f64)]) -> Vec<Vec<f64>> {
+        points
+            .iter()
+            .map(|a| {
+                points
+                    .iter()
+                    .map(|b| (a.0 - b.0).hypot(a.1 - b.1))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exact_and_heuristic_orders_agree_on_an_arc() {
+        // The route points lie on an arc from the start to the end, given out of order, so the
+        // cheapest order visits them along the arc.
+        let angles = [5, 1, 7, 3, 6, 2, 4];
+        let mut points = vec![(-1.0, 0.0)];
+        points.extend(angles.iter().map(|&step| {
+            let angle = ::std::f64::consts::PI * (1.0 - f64::from(step) / 8.0);
+            (angle.cos(), angle.sin())
+        }));
+        points.push((1.0, 0.0));
+        let costs = distances(&points);
+
+        let exact = held_karp(&costs, angles.len());
+        assert_eq!(exact, vec![2, 6, 4, 7, 1, 5, 3]);
+        assert_eq!(heuristic(&costs, angles.len()), exact);
+    }
+
+    #[test]
+    fn heuristic_orders_are_never_cheaper_than_exact_ones() {
+        let mut rng = Rng::new(19);
+        for count in 0..9 {
+            for _ in 0..20 {
+                let points = (0..(count + 2))
+                    .map(|_| (rng.range(0, 100) as f64, rng.range(0, 100) as f64))
+                    .collect::<Vec<_>>();
+                let costs = distances(&points);
+                let exact = tour_cost(&costs, &held_karp(&costs, count));
+                let heuristic = tour_cost(&costs, &heuristic(&costs, count));
+                assert!(exact <= heuristic + IMPROVEMENT, "{:?}", points);
+            }
+        }
+    }
+
+    #[test]
+    fn heuristic_orders_cannot_be_improved_by_any_single_move() {
+        // Costs that differ each way, with some legs that cannot be made, checked against every
+        // reversal and every moved run costed in full.
+        let mut rng = Rng::new(190);
+        for count in 2..16 {
+            for _ in 0..10 {
+                let costs = (0..(count + 2))
+                    .map(|_| {
+                        (0..(count + 2))
+                            .map(|_| match rng.range(0, 20) {
+                                0 => f64::INFINITY,
+                                cost => cost as f64,
+                            })
+                            .collect()
+                    })
+                    .collect::<Vec<Vec<_>>>();
+                let tour = heuristic(&costs, count);
+                let cost = tour_cost(&costs, &tour);
+                let mut sorted = tour.clone();
+                sorted.sort();
+                assert_eq!(sorted, (1..(count + 1)).collect::<Vec<_>>());
+
+                for first in 0..count {
+                    for last in (first + 1)..count {
+                        let mut reversed = tour.clone();
+                        reversed[first..(last + 1)].reverse();
+                        assert!(tour_cost(&costs, &reversed) >= cost - IMPROVEMENT);
+                    }
+                }
+                for length in 1..4 {
+                    for start in 0..(count.saturating_sub(length - 1)) {
+                        for position in 0..(count - length + 1) {
+                            let mut moved = tour.clone();
+                            let run = moved.drain(start..(start + length)).collect::<Vec<_>>();
+                            moved.splice(position..position, run);
+                            assert!(tour_cost(&costs, &moved) >= cost - IMPROVEMENT);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
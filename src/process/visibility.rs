@@ -0,0 +1,175 @@
+//! Provides an alternative to the convex hull heuristic, finding true shortest paths by searching
+//! a visibility graph.
+
+use error::Error;
+use io::input::Input;
+use io::output::Output;
+use shape::coord::Coord;
+use shape::index::ObstacleIndex;
+use shape::path::{distance, Path};
+use shape::scalar::Scalar;
+use shape::segment::Segment;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+
+/// Processes the input into it's output by searching the visibility graph for each leg.
+///
+/// The output contains no hulls, only paths.
+pub fn process<T: Scalar>(input: &Input<T>) -> Result<Output<T>, Error> {
+    let (prepared, snaps) = super::prepare(input)?;
+    let graph = VisibilityGraph::from_input(&prepared);
+
+    let (path, order) = super::order_waypoints(&prepared, |origin, destination| {
+        graph
+            .shortest_path(origin, destination)
+            .map_or(f64::INFINITY, |path| path.length)
+    });
+
+    let legs = path.windows(2).collect::<Vec<_>>();
+    let paths = super::map_in_order(&legs, |leg| graph.shortest_path(leg[0], leg[1]))
+        .into_iter()
+        .enumerate()
+        .map(|(leg, path)| path.ok_or(Error::Unreachable { leg }))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Output {
+        input: input.clone(),
+        obstacles: prepared.polygons,
+        hulls: Vec::new(),
+        paths,
+        snaps,
+        order,
+        // Only the hull planner grows hulls to trace.
+        trace: None,
+    })
+}
+
+/// A graph connecting every pair of mutually visible points among the polygon vertices and
+/// waypoints of an input.
+#[derive(Debug, Clone)]
+pub struct VisibilityGraph<T = i64> {
+    /// The points that make up the nodes of the graph.
+    pub nodes: Vec<Coord<T>>,
+
+    /// For each node, the nodes visible from it and the distance to them.
+    pub edges: Vec<Vec<(usize, f64)>>,
+}
+
+impl<T: Scalar> VisibilityGraph<T> {
+    /// Constructs the visibility graph over the polygon and hole vertices and waypoints of an
+    /// input.
+    pub fn from_input(input: &Input<T>) -> VisibilityGraph<T> {
+        let mut nodes = Vec::new();
+        let mut indices = HashMap::new();
+        let waypoints = Some(input.start)
+            .into_iter()
+            .chain(Some(input.end))
+            .chain(input.route.iter().cloned());
+        let polypoints = input
+            .polygons
+            .iter()
+            .flat_map(|polygon| Some(polygon).into_iter().chain(polygon.holes.iter()))
+            .flat_map(|polygon| polygon.points.iter().cloned());
+        for coord in waypoints.chain(polypoints) {
+            indices.entry(coord).or_insert_with(|| {
+                nodes.push(coord);
+                nodes.len() - 1
+            });
+        }
+
+        let obstacles = ObstacleIndex::new(&input.polygons);
+
+        let mut edges = vec![Vec::new(); nodes.len()];
+        for i in 0..nodes.len() {
+            for j in (i + 1)..nodes.len() {
+                if is_visible(nodes[i], nodes[j], &obstacles) {
+                    let length = distance(nodes[i], nodes[j]);
+                    edges[i].push((j, length));
+                    edges[j].push((i, length));
+                }
+            }
+        }
+
+        VisibilityGraph { nodes, edges }
+    }
+
+    /// Finds the shortest path between two nodes of the graph using A*.
+    ///
+    /// Returns `None` if either point is not a node, or no path exists.
+    pub fn shortest_path(&self, origin: Coord<T>, destination: Coord<T>) -> Option<Path<T>> {
+        let start = self.nodes.iter().position(|&node| node == origin)?;
+        let goal = self.nodes.iter().position(|&node| node == destination)?;
+
+        let mut cost = vec![f64::INFINITY; self.nodes.len()];
+        let mut previous = vec![None; self.nodes.len()];
+        let mut open = BinaryHeap::new();
+
+        cost[start] = 0.0;
+        open.push(Candidate {
+            estimate: distance(origin, destination),
+            node: start,
+        });
+
+        while let Some(Candidate { node, .. }) = open.pop() {
+            if node == goal {
+                let mut points = vec![self.nodes[goal]];
+                let mut current = goal;
+                while let Some(before) = previous[current] {
+                    points.push(self.nodes[before]);
+                    current = before;
+                }
+                points.reverse();
+                return Some(Path::from_points(points));
+            }
+
+            for &(next, length) in &self.edges[node] {
+                let next_cost = cost[node] + length;
+                if next_cost < cost[next] {
+                    cost[next] = next_cost;
+                    previous[next] = Some(node);
+                    open.push(Candidate {
+                        estimate: next_cost + distance(self.nodes[next], destination),
+                        node: next,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// An entry in the A* open set, ordered so the lowest estimate is popped first.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Candidate {
+    estimate: f64,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        other
+            .estimate
+            .partial_cmp(&self.estimate)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Checks if there is line of sight between two points around a set of obstacles.
+///
+/// Sight lines may touch obstacles or run along their edges, but not pass through them.
+fn is_visible<T: Scalar>(a: Coord<T>, b: Coord<T>, obstacles: &ObstacleIndex<T>) -> bool {
+    obstacles
+        .intersecting_polygons(&Segment::from_coords(a, b))
+        .is_empty()
+}
@@ -0,0 +1,468 @@
+//! Provides boolean operations between polygons, their union, intersection and difference.
+//!
+//! Both polygons are split at every point where their boundaries cross or touch, and each piece
+//! of boundary is kept or dropped by whether it lies inside the other polygon. The kept pieces are
+//! then joined back into rings. Where boundaries cross between integer coordinates, the crossing is
+//! rounded to the nearest value.
+
+use shape::containment::Containment;
+use shape::coord::Coord;
+use shape::orientation::Orientation;
+use shape::polygon::Polygon;
+use shape::scalar::Scalar;
+use shape::segment::Segment;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A directed edge, from it's first coordinate to it's second.
+type Edge<T> = (Coord<T>, Coord<T>);
+
+/// A boolean operation between two polygons.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+/// Where a piece of one polygon's boundary lies relative to the other polygon.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Side {
+    Inside,
+    Outside,
+    /// Along the other polygon's boundary, running the same way.
+    Same,
+    /// Along the other polygon's boundary, running the opposite way.
+    Opposite,
+}
+
+impl<T: Scalar> Polygon<T> {
+    /// Computes the area covered by either polygon, as polygons that may have holes.
+    ///
+    /// Polygons that overlap or share part of an edge merge into one, while those that are apart
+    /// or touch only at points are returned separately.
+    pub fn union(&self, other: &Polygon<T>) -> Vec<Polygon<T>> {
+        combine(self, other, Operation::Union)
+    }
+
+    /// Computes the area covered by both polygons, as polygons that may have holes.
+    pub fn intersection(&self, other: &Polygon<T>) -> Vec<Polygon<T>> {
+        combine(self, other, Operation::Intersection)
+    }
+
+    /// Computes the area covered by this polygon but not the other, as polygons that may have
+    /// holes.
+    pub fn difference(&self, other: &Polygon<T>) -> Vec<Polygon<T>> {
+        combine(self, other, Operation::Difference)
+    }
+}
+
+/// Performs a boolean operation between two polygons.
+fn combine<T: Scalar>(a: &Polygon<T>, b: &Polygon<T>, operation: Operation) -> Vec<Polygon<T>> {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    a.normalize();
+    b.normalize();
+
+    let (a_edges, b_edges) = split_edges(&a, &b);
+    let a_set = a_edges.iter().cloned().collect::<BTreeSet<_>>();
+    let b_set = b_edges.iter().cloned().collect::<BTreeSet<_>>();
+
+    let mut kept = Vec::new();
+    for &(p, q) in &a_edges {
+        match (operation, side(p, q, &b, &b_set)) {
+            (Operation::Union, Side::Outside)
+            | (Operation::Union, Side::Same)
+            | (Operation::Intersection, Side::Inside)
+            | (Operation::Intersection, Side::Same)
+            | (Operation::Difference, Side::Outside)
+            | (Operation::Difference, Side::Opposite) => kept.push((p, q)),
+            _ => {}
+        }
+    }
+    // Edges shared with the first polygon have already been decided.
+    for &(p, q) in &b_edges {
+        match (operation, side(p, q, &a, &a_set)) {
+            (Operation::Union, Side::Outside) | (Operation::Intersection, Side::Inside) => {
+                kept.push((p, q))
+            }
+            // The inside of the second polygon becomes outside, so it's edges turn around.
+            (Operation::Difference, Side::Inside) => kept.push((q, p)),
+            _ => {}
+        }
+    }
+
+    assemble(link_rings(kept))
+}
+
+/// Splits the edges of two polygons, including those of their holes, at every point where they
+/// meet the edges of the other, returning the pieces in the direction of their polygon.
+fn split_edges<T: Scalar>(a: &Polygon<T>, b: &Polygon<T>) -> (Vec<Edge<T>>, Vec<Edge<T>>) {
+    let a_edges = directed_edges(a);
+    let b_edges = directed_edges(b);
+    let mut a_splits = vec![Vec::new(); a_edges.len()];
+    let mut b_splits = vec![Vec::new(); b_edges.len()];
+
+    for (i, &(p, q)) in a_edges.iter().enumerate() {
+        let first = Segment::from_coords(p, q);
+        for (j, &(r, s)) in b_edges.iter().enumerate() {
+            let second = Segment::from_coords(r, s);
+            // Where an end of one edge lies on the other, including along a shared line.
+            for &end in &[r, s] {
+                if first.contains_colinear_coord(end) {
+                    a_splits[i].push(end);
+                }
+            }
+            for &end in &[p, q] {
+                if second.contains_colinear_coord(end) {
+                    b_splits[j].push(end);
+                }
+            }
+            if let Some(crossing) = crossing(p, q, r, s) {
+                a_splits[i].push(crossing);
+                b_splits[j].push(crossing);
+            }
+        }
+    }
+
+    (split_at(&a_edges, &a_splits), split_at(&b_edges, &b_splits))
+}
+
+/// Lists the edges of a polygon and it's holes, in the direction of their ring.
+fn directed_edges<T: Scalar>(polygon: &Polygon<T>) -> Vec<Edge<T>> {
+    Some(&polygon.points)
+        .into_iter()
+        .chain(polygon.holes.iter().map(|hole| &hole.points))
+        .flat_map(|ring| (0..ring.len()).map(move |i| (ring[i], ring[(i + 1) % ring.len()])))
+        .filter(|&(p, q)| p != q)
+        .collect()
+}
+
+/// Cuts each edge at it's split points, in order along the edge.
+fn split_at<T: Scalar>(edges: &[Edge<T>], splits: &[Vec<Coord<T>>]) -> Vec<Edge<T>> {
+    let mut pieces = Vec::new();
+    for (&(p, q), splits) in edges.iter().zip(splits) {
+        let along = |coord: Coord<T>| {
+            (coord.x.to_f64() - p.x.to_f64()) * (q.x.to_f64() - p.x.to_f64())
+                + (coord.y.to_f64() - p.y.to_f64()) * (q.y.to_f64() - p.y.to_f64())
+        };
+        let mut points = splits.clone();
+        points.push(p);
+        points.push(q);
+        points.sort_by(|&m, &n| along(m).total_cmp(&along(n)));
+        points.dedup();
+        for pair in points.windows(2) {
+            pieces.push((pair[0], pair[1]));
+        }
+    }
+    pieces
+}
+
+/// Finds the point where two edges cross at a single point inside both of them.
+fn crossing<T: Scalar>(p: Coord<T>, q: Coord<T>, r: Coord<T>, s: Coord<T>) -> Option<Coord<T>> {
+    let o1 = Orientation::from_coords(p, q, r);
+    let o2 = Orientation::from_coords(p, q, s);
+    let o3 = Orientation::from_coords(r, s, p);
+    let o4 = Orientation::from_coords(r, s, q);
+    if o1.is_colinear() || o2.is_colinear() || o3.is_colinear() || o4.is_colinear() {
+        return None;
+    }
+    if o1 == o2 || o3 == o4 {
+        return None;
+    }
+
+    let (px, py) = (p.x.to_f64(), p.y.to_f64());
+    let (dx, dy) = (q.x.to_f64() - px, q.y.to_f64() - py);
+    let (ex, ey) = (s.x.to_f64() - r.x.to_f64(), s.y.to_f64() - r.y.to_f64());
+    let t = ((r.x.to_f64() - px) * ey - (r.y.to_f64() - py) * ex) / (dx * ey - dy * ex);
+    Some(Coord {
+        x: T::from_f64(px + t * dx),
+        y: T::from_f64(py + t * dy),
+    })
+}
+
+/// Decides where a piece of boundary lies relative to the other polygon.
+fn side<T: Scalar>(
+    p: Coord<T>,
+    q: Coord<T>,
+    other: &Polygon<T>,
+    other_edges: &BTreeSet<Edge<T>>,
+) -> Side {
+    if other_edges.contains(&(p, q)) {
+        return Side::Same;
+    }
+    if other_edges.contains(&(q, p)) {
+        return Side::Opposite;
+    }
+    // Work in doubled coordinates so the midpoint stays integral.
+    match other.doubled().contains(p.sum(q)) {
+        Containment::Inside => Side::Inside,
+        // A piece that only partly follows the other boundary, after rounding.
+        Containment::Boundary | Containment::Outside => Side::Outside,
+    }
+}
+
+/// Joins directed edges into closed rings, turning as sharply as possible toward the inside
+/// wherever several edges leave the same point, so rings that touch at a point stay apart.
+fn link_rings<T: Scalar>(edges: Vec<Edge<T>>) -> Vec<Vec<Coord<T>>> {
+    let mut outgoing: BTreeMap<Coord<T>, Vec<usize>> = BTreeMap::new();
+    for (index, &(p, _)) in edges.iter().enumerate() {
+        outgoing.entry(p).or_default().push(index);
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut ring = vec![edges[first].0];
+        let mut current = first;
+        loop {
+            let (p, q) = edges[current];
+            let next = outgoing
+                .get(&q)
+                .into_iter()
+                .flat_map(|candidates| candidates.iter().cloned())
+                .filter(|&candidate| !used[candidate] || candidate == first)
+                .min_by(|&m, &n| turn(p, q, edges[m].1).total_cmp(&turn(p, q, edges[n].1)));
+            match next {
+                Some(next) if next == first => {
+                    rings.push(ring);
+                    break;
+                }
+                Some(next) => {
+                    used[next] = true;
+                    ring.push(edges[next].0);
+                    current = next;
+                }
+                // Rounding left the ring open, so it is dropped.
+                None => break,
+            }
+        }
+    }
+    rings
+}
+
+/// The angle turned from the direction `p` to `q` onto the direction `q` to `r`, lowest when
+/// turning furthest toward the inside of a normalized ring.
+fn turn<T: Scalar>(p: Coord<T>, q: Coord<T>, r: Coord<T>) -> f64 {
+    let (dx, dy) = (q.x.to_f64() - p.x.to_f64(), q.y.to_f64() - p.y.to_f64());
+    let (ex, ey) = (r.x.to_f64() - q.x.to_f64(), r.y.to_f64() - q.y.to_f64());
+    // Normalized rings are clockwise with the y axis pointing up, keeping their inside on the
+    // right, so the inside is toward negative angles.
+    (dx * ey - dy * ex).atan2(dx * ex + dy * ey)
+}
+
+/// Groups rings into polygons, each hole inside the smallest outer ring that contains it.
+fn assemble<T: Scalar>(rings: Vec<Vec<Coord<T>>>) -> Vec<Polygon<T>> {
+    let mut outers = Vec::new();
+    let mut holes = Vec::new();
+    for ring in rings {
+        let ring = remove_straight_vertices(ring);
+        if ring.len() < 3 {
+            continue;
+        }
+        // Outer rings are clockwise with the y axis pointing up, and holes counterclockwise.
+        let area = signed_area(&ring);
+        if area < 0.0 {
+            outers.push((area.abs(), Polygon::from_points(ring)));
+        } else if area > 0.0 {
+            holes.push(Polygon::from_points(ring));
+        }
+    }
+    outers.sort_by(|m, n| m.0.total_cmp(&n.0));
+
+    for hole in holes {
+        let owner = outers.iter().position(|(_, outer)| {
+            let inside = hole
+                .points
+                .iter()
+                .map(|&point| outer.contains(point))
+                .find(|&containment| containment != Containment::Boundary);
+            inside == Some(Containment::Inside)
+        });
+        if let Some(owner) = owner {
+            outers[owner].1.holes.push(hole);
+        }
+    }
+
+    outers
+        .into_iter()
+        .map(|(_, mut polygon)| {
+            polygon.normalize();
+            polygon
+        })
+        .collect()
+}
+
+/// Removes the vertices where a ring carries straight on, left where edges were split.
+fn remove_straight_vertices<T: Scalar>(mut ring: Vec<Coord<T>>) -> Vec<Coord<T>> {
+    let mut i = 0;
+    while ring.len() > 2 && i < ring.len() {
+        let len = ring.len();
+        let (previous, current, next) = (ring[(i + len - 1) % len], ring[i], ring[(i + 1) % len]);
+        if Orientation::from_coords(previous, current, next).is_colinear()
+            && Segment::from_coords(previous, next).contains_colinear_coord(current)
+        {
+            ring.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    ring
+}
+
+/// Twice the signed area of a ring, positive when counterclockwise with the y axis pointing up.
+fn signed_area<T: Scalar>(ring: &[Coord<T>]) -> f64 {
+    (0..ring.len())
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            a.x.to_f64() * b.y.to_f64() - b.x.to_f64() * a.y.to_f64()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ring through coordinates, starting from it's least.
+    fn ring(points: &[(i64, i64)]) -> Vec<Coord> {
+        let mut ring = points
+            .iter()
+            .map(|&(x, y)| Coord { x, y })
+            .collect::<Vec<_>>();
+        let least = (0..ring.len()).min_by_key(|&i| ring[i]).unwrap_or(0);
+        ring.rotate_left(least);
+        ring
+    }
+
+    /// A square with corners at `low` and `high` on both axes.
+    fn square(low: i64, high: i64) -> Polygon {
+        Polygon::from_points(ring(&[(low, low), (high, low), (high, high), (low, high)]))
+    }
+
+    /// The rings of polygons, each outer ring with it's holes, starting from their least
+    /// coordinates and in order, so results can be compared whatever vertex they start from.
+    fn rings(polygons: Vec<Polygon>) -> Vec<(Vec<Coord>, Vec<Vec<Coord>>)> {
+        let start = |points: &[Coord]| {
+            ring(
+                &points
+                    .iter()
+                    .map(|coord| (coord.x, coord.y))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let mut rings = polygons
+            .iter()
+            .map(|polygon| {
+                let mut holes = polygon
+                    .holes
+                    .iter()
+                    .map(|hole| start(&hole.points))
+                    .collect::<Vec<_>>();
+                holes.sort();
+                (start(&polygon.points), holes)
+            })
+            .collect::<Vec<_>>();
+        rings.sort();
+        rings
+    }
+
+    /// The rings of a polygon without holes, wound as a normalized polygon.
+    fn outline(points: &[(i64, i64)]) -> (Vec<Coord>, Vec<Vec<Coord>>) {
+        (ring(points), Vec::new())
+    }
+
+    #[test]
+    fn overlapping_squares() {
+        let (a, b) = (square(0, 10), square(5, 15));
+        assert_eq!(
+            rings(a.union(&b)),
+            vec![outline(&[
+                (0, 0),
+                (0, 10),
+                (5, 10),
+                (5, 15),
+                (15, 15),
+                (15, 5),
+                (10, 5),
+                (10, 0),
+            ])]
+        );
+        assert_eq!(
+            rings(a.intersection(&b)),
+            vec![outline(&[(5, 5), (5, 10), (10, 10), (10, 5)])]
+        );
+        assert_eq!(
+            rings(a.difference(&b)),
+            vec![outline(&[
+                (0, 0),
+                (0, 10),
+                (5, 10),
+                (5, 5),
+                (10, 5),
+                (10, 0)
+            ])]
+        );
+    }
+
+    #[test]
+    fn nested_squares() {
+        let (outer, inner) = (square(0, 10), square(3, 6));
+        let whole = outline(&[(0, 0), (0, 10), (10, 10), (10, 0)]);
+        let small = outline(&[(3, 3), (3, 6), (6, 6), (6, 3)]);
+        assert_eq!(rings(outer.union(&inner)), vec![whole.clone()]);
+        assert_eq!(rings(outer.intersection(&inner)), vec![small]);
+        // Cutting the inner square out of the outer leaves a hole, wound the other way.
+        assert_eq!(
+            rings(outer.difference(&inner)),
+            vec![(whole.0, vec![ring(&[(3, 3), (6, 3), (6, 6), (3, 6)])])]
+        );
+        assert_eq!(rings(inner.difference(&outer)), Vec::new());
+    }
+
+    #[test]
+    fn squares_sharing_an_edge() {
+        let a = square(0, 10);
+        let b = Polygon::from_points(ring(&[(10, 0), (20, 0), (20, 10), (10, 10)]));
+        assert_eq!(
+            rings(a.union(&b)),
+            vec![outline(&[(0, 0), (0, 10), (20, 10), (20, 0)])]
+        );
+        assert_eq!(rings(a.intersection(&b)), Vec::new());
+        assert_eq!(
+            rings(a.difference(&b)),
+            vec![outline(&[(0, 0), (0, 10), (10, 10), (10, 0)])]
+        );
+    }
+
+    #[test]
+    fn squares_touching_at_a_corner() {
+        let (a, b) = (square(0, 10), square(10, 20));
+        let first = outline(&[(0, 0), (0, 10), (10, 10), (10, 0)]);
+        let second = outline(&[(10, 10), (10, 20), (20, 20), (20, 10)]);
+        assert_eq!(rings(a.union(&b)), vec![first.clone(), second]);
+        assert_eq!(rings(a.intersection(&b)), Vec::new());
+        assert_eq!(rings(a.difference(&b)), vec![first]);
+    }
+
+    #[test]
+    fn identical_squares() {
+        let a = square(0, 10);
+        let whole = outline(&[(0, 0), (0, 10), (10, 10), (10, 0)]);
+        assert_eq!(rings(a.union(&a)), vec![whole.clone()]);
+        assert_eq!(rings(a.intersection(&a)), vec![whole]);
+        assert_eq!(rings(a.difference(&a)), Vec::new());
+    }
+
+    #[test]
+    fn disjoint_squares() {
+        let (a, b) = (square(0, 10), square(20, 30));
+        let first = outline(&[(0, 0), (0, 10), (10, 10), (10, 0)]);
+        let second = outline(&[(20, 20), (20, 30), (30, 30), (30, 20)]);
+        assert_eq!(rings(a.union(&b)), vec![first.clone(), second]);
+        assert_eq!(rings(a.intersection(&b)), Vec::new());
+        assert_eq!(rings(a.difference(&b)), vec![first]);
+    }
+}
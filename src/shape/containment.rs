@@ -0,0 +1,389 @@
+//! Provides the Containment and Contact enums, classifying coordinates and segments against
+//! polygons.
+
+use shape::coord::Coord;
+use shape::intersection::Intersection;
+use shape::orientation::Orientation;
+use shape::polygon::Polygon;
+use shape::scalar::Scalar;
+use shape::segment::Segment;
+
+/// Represents where a coordinate lies relative to a polygon.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Containment {
+    /// The coordinate lies strictly inside the polygon, and outside all of it's holes.
+    Inside,
+
+    /// The coordinate lies on the boundary of the polygon or one of it's holes.
+    Boundary,
+
+    /// The coordinate lies outside the polygon, or strictly inside one of it's holes.
+    Outside,
+}
+
+/// Represents how a line segment meets a polygon.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Contact {
+    /// The segment does not meet the polygon.
+    Disjoint,
+
+    /// The segment meets the boundary of the polygon or it's holes only at isolated points, such
+    /// as a vertex or it's own ends.
+    Touching,
+
+    /// The segment runs along the boundary of the polygon or it's holes, without entering it.
+    Grazing,
+
+    /// The segment passes through the interior of the polygon.
+    Penetrating,
+}
+
+impl<T: Scalar> Polygon<T> {
+    /// Classifies a coordinate as inside, on the boundary of, or outside the polygon.
+    ///
+    /// Counts the edges crossing the ray from the coordinate towards increasing x, deciding each
+    /// crossing with an orientation test, so it is exact for integer coordinates.
+    pub fn contains(&self, coord: Coord<T>) -> Containment {
+        let mut inside = false;
+        for segment in self.segments() {
+            if segment.contains_colinear_coord(coord) {
+                return Containment::Boundary;
+            }
+            let (p, q) = (segment.a, segment.b);
+            if (p.y > coord.y) != (q.y > coord.y) {
+                // The edge crosses the ray if the coordinate lies to the left of it, walking from
+                // its lower end to its higher with the y axis pointing up.
+                let (low, high) = if p.y < q.y { (p, q) } else { (q, p) };
+                if Orientation::from_coords(low, high, coord) == Orientation::Clockwise {
+                    inside = !inside;
+                }
+            }
+        }
+        if inside {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        }
+    }
+
+    /// Finds the closest point to a coordinate that lies on the boundary of the polygon or one of
+    /// it's holes, and not inside the polygon.
+    ///
+    /// For integer coordinates the closest point is rounded to a neighbouring value that is not
+    /// inside, so may lie just outside the boundary instead. Returns `None` if the polygon has no
+    /// points.
+    pub fn nearest_boundary_point(&self, coord: Coord<T>) -> Option<Coord<T>> {
+        let (x, y) = (coord.x.to_f64(), coord.y.to_f64());
+        let distance = |point: Coord<T>| (point.x.to_f64() - x).hypot(point.y.to_f64() - y);
+
+        let mut nearest = None;
+        let mut nearest_gap = f64::INFINITY;
+        for segment in self.segments() {
+            let (ax, ay) = (segment.a.x.to_f64(), segment.a.y.to_f64());
+            let (dx, dy) = (segment.b.x.to_f64() - ax, segment.b.y.to_f64() - ay);
+            let length = dx * dx + dy * dy;
+            let along = (x - ax) * dx + (y - ay) * dy;
+            let t = if length <= 0.0 || along <= 0.0 {
+                0.0
+            } else if along >= length {
+                1.0
+            } else {
+                along / length
+            };
+            let closest = (ax + t * dx, ay + t * dy);
+            let gap = (closest.0 - x).hypot(closest.1 - y);
+            if nearest.is_none() || gap < nearest_gap {
+                nearest = Some((closest, segment));
+                nearest_gap = gap;
+            }
+        }
+        let ((px, py), segment) = nearest?;
+
+        // The rounded closest point, then the values around it, then the ends of it's segment,
+        // which lie on the boundary.
+        let mut candidates = vec![Coord {
+            x: T::from_f64(px),
+            y: T::from_f64(py),
+        }];
+        for &cx in &[px.floor(), px.ceil()] {
+            for &cy in &[py.floor(), py.ceil()] {
+                candidates.push(Coord {
+                    x: T::from_f64(cx),
+                    y: T::from_f64(cy),
+                });
+            }
+        }
+        candidates.sort_by(|&p, &q| distance(p).total_cmp(&distance(q)));
+        candidates.push(segment.a);
+        candidates.push(segment.b);
+        candidates
+            .into_iter()
+            .find(|&candidate| self.contains(candidate) != Containment::Inside)
+    }
+
+    /// Classifies how a line segment meets the polygon.
+    ///
+    /// A segment penetrates the polygon if any part of it lies strictly inside, even if it only
+    /// joins two vertices of the polygon or passes through a vertex into the interior.
+    pub fn contact(&self, segment: &Segment<T>) -> Contact {
+        if segment.a == segment.b {
+            return match self.contains(segment.a) {
+                Containment::Inside => Contact::Penetrating,
+                Containment::Boundary => Contact::Touching,
+                Containment::Outside => Contact::Disjoint,
+            };
+        }
+
+        // Cut the segment wherever it meets the boundary, so each piece between cuts lies
+        // entirely inside, outside or along the boundary.
+        let mut cuts = vec![segment.a, segment.b];
+        let mut overlaps = Vec::new();
+        for edge in self.segments() {
+            match segment.intersection(&edge) {
+                Intersection::None => {}
+                Intersection::Point(_) => return Contact::Penetrating,
+                Intersection::Touching(coord) | Intersection::TouchingEndpoint(coord) => {
+                    cuts.push(coord)
+                }
+                Intersection::Overlap(overlap) => {
+                    cuts.push(overlap.a);
+                    cuts.push(overlap.b);
+                    overlaps.push(overlap);
+                }
+            }
+        }
+        let touching = cuts.len() > 2;
+        cuts.sort();
+        cuts.dedup();
+
+        let doubled = self.doubled();
+        let mut grazing = false;
+        for piece in cuts.windows(2) {
+            let (p, q) = (piece[0], piece[1]);
+            if overlaps
+                .iter()
+                .any(|overlap| overlap.a <= p && q <= overlap.b)
+            {
+                grazing = true;
+                continue;
+            }
+            // Work in doubled coordinates so the midpoint stays integral.
+            match doubled.contains(p.sum(q)) {
+                Containment::Inside => return Contact::Penetrating,
+                // Rounding can put the midpoint of a floating point piece on the boundary.
+                Containment::Boundary => grazing = true,
+                Containment::Outside => {}
+            }
+        }
+
+        if grazing {
+            Contact::Grazing
+        } else if touching {
+            Contact::Touching
+        } else {
+            Contact::Disjoint
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::Rng;
+
+    /// A coordinate.
+    fn coord(x: i64, y: i64) -> Coord {
+        Coord { x, y }
+    }
+
+    #[test]
+    fn contains_is_exact_near_a_long_edge() {
+        let triangle = Polygon::from_points(vec![coord(0, 0), coord(1 << 60, 1), coord(0, 2)]);
+        assert_eq!(
+            triangle.contains(coord((1 << 60) - 1, 1)),
+            Containment::Inside
+        );
+        assert_eq!(triangle.contains(coord(1 << 60, 1)), Containment::Boundary);
+        assert_eq!(
+            triangle.contains(coord((1 << 60) + 1, 1)),
+            Containment::Outside
+        );
+    }
+
+    #[test]
+    fn contains_classifies_points_around_a_square_with_a_hole() {
+        let mut square =
+            Polygon::from_points(vec![coord(0, 0), coord(10, 0), coord(10, 10), coord(0, 10)]);
+        square.holes.push(Polygon::from_points(vec![
+            coord(4, 4),
+            coord(4, 6),
+            coord(6, 6),
+            coord(6, 4),
+        ]));
+        for &(point, containment) in &[
+            (coord(2, 2), Containment::Inside),
+            (coord(2, 5), Containment::Inside),
+            (coord(5, 5), Containment::Outside),
+            (coord(11, 5), Containment::Outside),
+            (coord(-1, 0), Containment::Outside),
+            (coord(10, 5), Containment::Boundary),
+            (coord(4, 5), Containment::Boundary),
+            (coord(6, 6), Containment::Boundary),
+        ] {
+            assert_eq!(square.contains(point), containment, "{:?}", point);
+        }
+    }
+
+    /// A square with corners at `low` and `high` on both axes.
+    fn square(low: i64, high: i64) -> Polygon {
+        Polygon::from_points(vec![
+            coord(low, low),
+            coord(high, low),
+            coord(high, high),
+            coord(low, high),
+        ])
+    }
+
+    /// Classifies how the segment between two coordinates meets a polygon.
+    fn contact(polygon: &Polygon, a: Coord, b: Coord) -> Contact {
+        polygon.contact(&Segment::from_coords(a, b))
+    }
+
+    #[test]
+    fn contact_grazing_a_vertex_touches() {
+        let square = square(0, 10);
+        assert_eq!(
+            contact(&square, coord(-5, 5), coord(5, -5)),
+            Contact::Touching
+        );
+        assert_eq!(
+            contact(&square, coord(-5, 5), coord(0, 0)),
+            Contact::Touching
+        );
+    }
+
+    #[test]
+    fn contact_sliding_along_an_edge_grazes() {
+        let square = square(0, 10);
+        assert_eq!(
+            contact(&square, coord(-5, 0), coord(15, 0)),
+            Contact::Grazing
+        );
+        assert_eq!(
+            contact(&square, coord(2, 10), coord(8, 10)),
+            Contact::Grazing
+        );
+        assert_eq!(
+            contact(&square, coord(0, 0), coord(10, 0)),
+            Contact::Grazing
+        );
+    }
+
+    #[test]
+    fn contact_entering_through_a_vertex_penetrates() {
+        let square = square(0, 10);
+        assert_eq!(
+            contact(&square, coord(-5, -5), coord(5, 5)),
+            Contact::Penetrating
+        );
+        assert_eq!(
+            contact(&square, coord(-5, -5), coord(15, 15)),
+            Contact::Penetrating
+        );
+        // Joining two vertices across the inside.
+        assert_eq!(
+            contact(&square, coord(0, 0), coord(10, 10)),
+            Contact::Penetrating
+        );
+    }
+
+    #[test]
+    fn contact_of_a_leg_ending_on_the_boundary() {
+        let square = square(0, 10);
+        assert_eq!(
+            contact(&square, coord(-5, 5), coord(0, 5)),
+            Contact::Touching
+        );
+        assert_eq!(
+            contact(&square, coord(5, 5), coord(0, 5)),
+            Contact::Penetrating
+        );
+        assert_eq!(
+            contact(&square, coord(-5, 5), coord(-1, 5)),
+            Contact::Disjoint
+        );
+    }
+
+    #[test]
+    fn contact_does_not_overflow_near_the_extremes() {
+        let (low, high) = (i64::MIN + 1, i64::MAX - 1);
+        let whole = square(low, high);
+        assert_eq!(
+            contact(&whole, coord(low, low), coord(high, high)),
+            Contact::Penetrating
+        );
+        assert_eq!(
+            contact(&whole, coord(low, low), coord(high, low)),
+            Contact::Grazing
+        );
+        assert_eq!(
+            contact(&whole, coord(low, high), coord(high, high)),
+            Contact::Grazing
+        );
+        let small = square(1 << 62, (1 << 62) + 10);
+        assert_eq!(
+            contact(&small, coord(1 << 62, 1 << 62), coord(i64::MAX, i64::MAX)),
+            Contact::Penetrating
+        );
+    }
+
+    #[test]
+    fn contact_does_not_overflow_in_the_wide_type() {
+        // The doubled extremes are the largest values the wide type of `i64` is made from, and
+        // classifying against them doubles them again.
+        let (low, high) = (i64::MIN, i64::MAX);
+        let whole = square(low, high).doubled();
+        let doubled = |x, y| coord(x, y).sum(coord(x, y));
+        let contact = |a, b| whole.contact(&Segment::from_coords(a, b));
+        assert_eq!(
+            contact(doubled(low, low), doubled(high, high)),
+            Contact::Penetrating
+        );
+        assert_eq!(
+            contact(doubled(low, low), doubled(high, low)),
+            Contact::Grazing
+        );
+        assert_eq!(
+            contact(doubled(low, high), doubled(high, high)),
+            Contact::Grazing
+        );
+        assert_eq!(
+            contact(coord(low, high).sum(coord(high, high)), doubled(high, low)),
+            Contact::Penetrating
+        );
+    }
+
+    #[test]
+    fn contains_agrees_with_the_sides_of_random_triangles() {
+        let mut rng = Rng::new(17);
+        for _ in 0..2000 {
+            let corners = (0..3)
+                .map(|_| coord(rng.extreme() / 4, rng.extreme() / 4))
+                .collect::<Vec<_>>();
+            let point = coord(rng.extreme() / 4, rng.extreme() / 4);
+            let sides = (0..3)
+                .map(|i| Orientation::from_coords(corners[i], corners[(i + 1) % 3], point))
+                .collect::<Vec<_>>();
+            if sides.iter().any(|side| side.is_colinear()) {
+                continue;
+            }
+            let expected = if sides[0] == sides[1] && sides[1] == sides[2] {
+                Containment::Inside
+            } else {
+                Containment::Outside
+            };
+            let triangle = Polygon::from_points(corners);
+            assert_eq!(triangle.contains(point), expected, "{:?}", triangle);
+        }
+    }
+}
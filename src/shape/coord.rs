@@ -1,10 +1,56 @@
 //! Provides the Coord struct.
 
+use shape::scalar::Scalar;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
 /// A coordinate in the cartesian plane.
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub struct Coord {
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct Coord<T = i64> {
     /// The x coordinate.
-    pub x: i64,
+    pub x: T,
     /// The y coordinate.
-    pub y: i64,
+    pub y: T,
+}
+
+impl<T: Scalar> PartialEq for Coord<T> {
+    fn eq(&self, other: &Coord<T>) -> bool {
+        self.x.total_cmp(&other.x) == Ordering::Equal
+            && self.y.total_cmp(&other.y) == Ordering::Equal
+    }
+}
+
+impl<T: Scalar> Eq for Coord<T> {}
+
+impl<T: Scalar> PartialOrd for Coord<T> {
+    fn partial_cmp(&self, other: &Coord<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders coordinates left to right, then bottom to top.
+impl<T: Scalar> Ord for Coord<T> {
+    fn cmp(&self, other: &Coord<T>) -> Ordering {
+        self.x
+            .total_cmp(&other.x)
+            .then_with(|| self.y.total_cmp(&other.y))
+    }
+}
+
+impl<T: Scalar> Hash for Coord<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.x.hash_scalar(state);
+        self.y.hash_scalar(state);
+    }
+}
+
+impl<T: Scalar> Coord<T> {
+    /// Adds two coordinates in the wide type of their values, so the sum cannot overflow. In
+    /// doubled coordinates, this is their midpoint.
+    pub fn sum(self, other: Coord<T>) -> Coord<T::Wide> {
+        Coord {
+            x: self.x.widen() + other.x.widen(),
+            y: self.y.widen() + other.y.widen(),
+        }
+    }
 }
@@ -1,17 +1,90 @@
 //! Provides the Hull struct.
 
+use shape::coord::Coord;
+use shape::orientation::Orientation;
+use shape::polygon::Polygon;
+use shape::scalar::Scalar;
 use shape::segment::Segment;
+use std::collections::HashMap;
 
-/// Represents a Convex Hull
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Hull {
-    /// The segments that constitute a hull.
-    pub segment_set: Vec<Segment>,
+/// Represents a Convex Hull, as a ring of it's vertices.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Hull<T = i64> {
+    /// The vertices of the hull, counterclockwise with the y axis pointing down like a normalized
+    /// polygon, and starting from the vertex with the least x, then the least y.
+    #[serde(rename = "point")]
+    pub points: Vec<Coord<T>>,
 }
 
-impl Hull {
-    /// Constructs a hull from it's segements.
-    pub fn from_segment_set(segment_set: Vec<Segment>) -> Hull {
-        Hull { segment_set }
+impl<T: Scalar> Hull<T> {
+    /// Constructs a hull from the vertices of a convex ring in either winding order.
+    pub fn from_vertices(mut vertices: Vec<Coord<T>>) -> Hull<T> {
+        let first = (0..vertices.len()).min_by_key(|&i| vertices[i]);
+        if let Some(first) = first {
+            vertices.rotate_left(first);
+        }
+
+        // The leftmost vertex is a corner, so the turn there gives the winding order.
+        let len = vertices.len();
+        if len > 2
+            && Orientation::from_coords(vertices[len - 1], vertices[0], vertices[1])
+                == Orientation::Clockwise
+        {
+            vertices[1..].reverse();
+        }
+        Hull { points: vertices }
+    }
+
+    /// Constructs a hull from it's segments in any order, by walking them into a ring.
+    ///
+    /// Returns `None` if the segments do not form a single ring.
+    pub fn from_segment_set(segment_set: Vec<Segment<T>>) -> Option<Hull<T>> {
+        let mut adjacent: HashMap<Coord<T>, Vec<Coord<T>>> = HashMap::new();
+        for segment in &segment_set {
+            adjacent.entry(segment.a).or_default().push(segment.b);
+            adjacent.entry(segment.b).or_default().push(segment.a);
+        }
+
+        let first = segment_set.first()?.a;
+        let mut ring = vec![first];
+        let mut previous = first;
+        let mut current = segment_set[0].b;
+        while current != first {
+            ring.push(current);
+            let next = match adjacent
+                .get(&current)?
+                .iter()
+                .find(|&&coord| coord != previous)
+            {
+                Some(&next) => next,
+                None if ring.len() == 2 => break,
+                None => return None,
+            };
+            previous = current;
+            current = next;
+            if ring.len() > adjacent.len() {
+                return None;
+            }
+        }
+        Some(Hull::from_vertices(ring))
+    }
+
+    /// The vertices of the hull, in order.
+    pub fn vertices(&self) -> &[Coord<T>] {
+        &self.points
+    }
+
+    /// The segments between consecutive vertices of the hull, in order.
+    pub fn segments(&self) -> Vec<Segment<T>> {
+        match self.points.len() {
+            // A hull of two points is a single segment, not two.
+            2 => vec![Segment::from_coords(self.points[0], self.points[1])],
+            _ => self.to_polygon().segments(),
+        }
+    }
+
+    /// Converts the hull into a polygon with the same vertices.
+    pub fn to_polygon(&self) -> Polygon<T> {
+        Polygon::from_points(self.points.clone())
     }
 }
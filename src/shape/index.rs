@@ -0,0 +1,434 @@
+//! Provides the ObstacleIndex struct, accelerating queries against many polygons.
+
+use shape::coord::Coord;
+use shape::polygon::Polygon;
+use shape::scalar::Scalar;
+use shape::segment::Segment;
+use std::collections::{BTreeSet, HashSet};
+
+/// A uniform grid over the edges of a set of polygons.
+///
+/// Built once, it answers which polygons a segment intersects by testing only the edges that
+/// share a grid cell with the segment, rather than every edge of every polygon.
+#[derive(Debug, Clone)]
+pub struct ObstacleIndex<T = i64> {
+    /// The indexed polygons.
+    polygons: Vec<Polygon<T>>,
+
+    /// The edges of each polygon, cached.
+    edges: Vec<Vec<Segment<T>>>,
+
+    /// The bounding box of each polygon.
+    bounds: Vec<Bounds>,
+
+    /// The bounding box of every polygon, the extent of the grid.
+    extent: Bounds,
+
+    /// The width and height of a cell.
+    cell_size: f64,
+
+    /// The number of cells along the x axis.
+    columns: usize,
+
+    /// The number of cells along the y axis.
+    rows: usize,
+
+    /// For each cell, row by row, the polygon and edge indices of the edges overlapping it.
+    cells: Vec<Vec<(usize, usize)>>,
+
+    /// For each cell, row by row, the indices of the polygons whose bounding box overlaps it.
+    covering: Vec<Vec<usize>>,
+}
+
+impl<T: Scalar> ObstacleIndex<T> {
+    /// Builds the index over a set of polygons.
+    pub fn new(polygons: &[Polygon<T>]) -> ObstacleIndex<T> {
+        let edges = polygons.iter().map(Polygon::segments).collect::<Vec<_>>();
+        let bounds = polygons
+            .iter()
+            .map(|polygon| Bounds::of(polygon.points.iter().cloned()))
+            .collect::<Vec<_>>();
+        let extent = bounds
+            .iter()
+            .fold(Bounds::empty(), |extent, bounds| extent.union(bounds));
+
+        // Aim for roughly one edge per cell.
+        let edge_count = edges.iter().map(Vec::len).sum::<usize>().max(1) as f64;
+        let width = extent.max_x - extent.min_x;
+        let height = extent.max_y - extent.min_y;
+        let cell_size = match (width * height / edge_count).sqrt() {
+            size if size > 0.0 => size,
+            _ => (width.max(height) / edge_count).max(1.0),
+        };
+        let columns = ((width / cell_size).floor() as usize + 1).min(edge_count as usize * 4);
+        let rows = ((height / cell_size).floor() as usize + 1).min(edge_count as usize * 4);
+
+        let mut index = ObstacleIndex {
+            polygons: polygons.to_vec(),
+            edges: Vec::new(),
+            bounds,
+            extent,
+            cell_size,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+            covering: vec![Vec::new(); columns * rows],
+        };
+        for (polygon, bounds) in index.bounds.iter().enumerate() {
+            for c in index.column(bounds.min_x)..(index.column(bounds.max_x) + 1) {
+                for r in index.row(bounds.min_y)..(index.row(bounds.max_y) + 1) {
+                    index.covering[r * columns + c].push(polygon);
+                }
+            }
+        }
+        for (polygon, polygon_edges) in edges.iter().enumerate() {
+            for (edge, segment) in polygon_edges.iter().enumerate() {
+                for cell in index.cells_along(segment) {
+                    index.cells[cell].push((polygon, edge));
+                }
+            }
+        }
+        index.edges = edges;
+        index
+    }
+
+    /// The indexed polygons.
+    pub fn polygons(&self) -> &[Polygon<T>] {
+        &self.polygons
+    }
+
+    /// The cached edges of the polygon at an index.
+    pub fn edges(&self, polygon: usize) -> &[Segment<T>] {
+        &self.edges[polygon]
+    }
+
+    /// Finds the indices of the polygons whose interior a segment passes through, in ascending
+    /// order.
+    pub fn intersecting_polygons(&self, segment: &Segment<T>) -> Vec<usize> {
+        // Only polygons with an edge meeting the segment, or around a segment lying wholly within
+        // one, can have their interior crossed.
+        let mut tested = HashSet::new();
+        let mut candidates = self
+            .polygons_around(segment.a)
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        for cell in self.cells_along(segment) {
+            for &(polygon, edge) in &self.cells[cell] {
+                if candidates.contains(&polygon) || !tested.insert((polygon, edge)) {
+                    continue;
+                }
+                if self.edges[polygon][edge].intersection(segment).is_some() {
+                    candidates.insert(polygon);
+                }
+            }
+        }
+        candidates
+            .into_iter()
+            .filter(|&polygon| segment.crosses_interior(&self.polygons[polygon]))
+            .collect()
+    }
+
+    /// Finds the coordinates of polygons whose interior the segment passes through.
+    pub fn intersecting_polygon_coords(&self, segment: &Segment<T>) -> BTreeSet<Coord<T>> {
+        self.intersecting_polygons(segment)
+            .into_iter()
+            .flat_map(|polygon| self.polygons[polygon].points.iter().cloned())
+            .collect()
+    }
+
+    /// Finds the indices of the polygons whose bounding box contains a coordinate, in ascending
+    /// order.
+    ///
+    /// Only the polygons whose bounding box overlaps the cell containing the coordinate are
+    /// tested.
+    pub fn polygons_around(&self, coord: Coord<T>) -> Vec<usize> {
+        let (x, y) = (coord.x.to_f64(), coord.y.to_f64());
+        if !self.extent.contains(x, y) {
+            return Vec::new();
+        }
+        self.covering[self.row(y) * self.columns + self.column(x)]
+            .iter()
+            .cloned()
+            .filter(|&polygon| self.bounds[polygon].contains(x, y))
+            .collect()
+    }
+
+    /// The column of cells containing a horizontal position, the outermost columns covering
+    /// everything beyond the extent.
+    fn column(&self, x: f64) -> usize {
+        (((x - self.extent.min_x) / self.cell_size).floor().max(0.0) as usize).min(self.columns - 1)
+    }
+
+    /// The row of cells containing a vertical position, the outermost rows covering everything
+    /// beyond the extent.
+    fn row(&self, y: f64) -> usize {
+        (((y - self.extent.min_y) / self.cell_size).floor().max(0.0) as usize).min(self.rows - 1)
+    }
+
+    /// The indices of the cells a segment passes through, walking it column by column.
+    ///
+    /// Within each column, only the rows between the heights of the segment at the sides of the
+    /// column are taken. Both are widened by a margin far larger than any rounding, so a cell the
+    /// segment touches is never missed, at the cost of sometimes taking a neighbouring one.
+    fn cells_along(&self, segment: &Segment<T>) -> Vec<usize> {
+        let bounds = Bounds::of(vec![segment.a, segment.b]);
+        if !self.extent.overlaps(&bounds) {
+            return Vec::new();
+        }
+        let margin = 1e-9 * (self.cell_size + self.extent.magnitude());
+
+        let (a, b) = (
+            (segment.a.x.to_f64(), segment.a.y.to_f64()),
+            (segment.b.x.to_f64(), segment.b.y.to_f64()),
+        );
+        let (left, right) = if a.0 <= b.0 { (a, b) } else { (b, a) };
+        // The height of the segment at a horizontal position, clamped to its ends.
+        let height = |x: f64| {
+            if right.0 > left.0 {
+                let t = ((x - left.0) / (right.0 - left.0)).clamp(0.0, 1.0);
+                left.1 + t * (right.1 - left.1)
+            } else {
+                left.1
+            }
+        };
+
+        // The left side of a column, with the outermost columns covering everything beyond the
+        // extent.
+        let side = |c: usize, outside: f64| {
+            if c == 0 || c == self.columns {
+                outside
+            } else {
+                self.extent.min_x + c as f64 * self.cell_size
+            }
+        };
+
+        let mut cells = Vec::new();
+        for c in self.column(bounds.min_x - margin)..(self.column(bounds.max_x + margin) + 1) {
+            let (low, high) = if right.0 > left.0 {
+                let (first, second) = (
+                    height(side(c, f64::NEG_INFINITY) - margin),
+                    height(side(c + 1, f64::INFINITY) + margin),
+                );
+                (first.min(second), first.max(second))
+            } else {
+                (bounds.min_y, bounds.max_y)
+            };
+            for r in self.row(low - margin)..(self.row(high + margin) + 1) {
+                cells.push(r * self.columns + c);
+            }
+        }
+        cells
+    }
+}
+
+/// An axis aligned bounding box.
+#[derive(Debug, Copy, Clone)]
+struct Bounds {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+}
+
+impl Bounds {
+    /// A bounding box containing nothing.
+    fn empty() -> Bounds {
+        Bounds {
+            min_x: f64::INFINITY,
+            min_y: f64::INFINITY,
+            max_x: f64::NEG_INFINITY,
+            max_y: f64::NEG_INFINITY,
+        }
+    }
+
+    /// The bounding box of a set of coordinates.
+    fn of<T: Scalar, I: IntoIterator<Item = Coord<T>>>(coords: I) -> Bounds {
+        coords.into_iter().fold(Bounds::empty(), |bounds, coord| {
+            let (x, y) = (coord.x.to_f64(), coord.y.to_f64());
+            Bounds {
+                min_x: bounds.min_x.min(x),
+                min_y: bounds.min_y.min(y),
+                max_x: bounds.max_x.max(x),
+                max_y: bounds.max_y.max(y),
+            }
+        })
+    }
+
+    /// The bounding box of both bounding boxes.
+    fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    /// The largest magnitude of any of its sides.
+    fn magnitude(&self) -> f64 {
+        self.min_x
+            .abs()
+            .max(self.min_y.abs())
+            .max(self.max_x.abs())
+            .max(self.max_y.abs())
+    }
+
+    /// Checks if two bounding boxes overlap, including touching.
+    fn overlaps(&self, other: &Bounds) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+
+    /// Checks if a point lies in the bounding box, including it's boundary.
+    fn contains(&self, x: f64, y: f64) -> bool {
+        self.min_x <= x && x <= self.max_x && self.min_y <= y && y <= self.max_y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::Rng;
+
+    /// Random rectangles and triangles, some overlapping, within a square of a size.
+    fn polygons(rng: &mut Rng, count: usize, size: i64) -> Vec<Polygon> {
+        (0..count)
+            .map(|_| {
+                let (x, y) = (rng.range(0, size), rng.range(0, size));
+                let (w, h) = (rng.range(1, size / 10), rng.range(1, size / 10));
+                let coord = |x, y| Coord { x, y };
+                let mut polygon = if rng.range(0, 1) == 0 {
+                    Polygon::from_points(vec![
+                        coord(x, y),
+                        coord(x + w, y),
+                        coord(x + w, y + h),
+                        coord(x, y + h),
+                    ])
+                } else {
+                    Polygon::from_points(vec![
+                        coord(x, y),
+                        coord(x + w, y + h / 2),
+                        coord(x, y + h),
+                    ])
+                };
+                polygon.normalize();
+                polygon
+            })
+            .collect()
+    }
+
+    /// A random segment, often axis aligned or along a diagonal, where cell corners are met.
+    fn segment(rng: &mut Rng, size: i64) -> Segment {
+        let a = Coord {
+            x: rng.range(-size / 10, size + size / 10),
+            y: rng.range(-size / 10, size + size / 10),
+        };
+        let length = rng.range(0, size / 2);
+        let b = match rng.range(0, 3) {
+            0 => Coord {
+                x: a.x + length,
+                y: a.y,
+            },
+            1 => Coord {
+                x: a.x,
+                y: a.y - length,
+            },
+            2 => Coord {
+                x: a.x - length,
+                y: a.y + length,
+            },
+            _ => Coord {
+                x: rng.range(-size / 10, size + size / 10),
+                y: rng.range(-size / 10, size + size / 10),
+            },
+        };
+        Segment::from_coords(a, b)
+    }
+
+    #[test]
+    fn intersecting_polygons_matches_a_linear_scan() {
+        let mut rng = Rng::new(11);
+        for &size in &[100, 1000, 1 << 40] {
+            for _ in 0..20 {
+                let polygons = polygons(&mut rng, 30, size);
+                let index = ObstacleIndex::new(&polygons);
+                for _ in 0..50 {
+                    let segment = segment(&mut rng, size);
+                    let expected = (0..polygons.len())
+                        .filter(|&polygon| segment.crosses_interior(&polygons[polygon]))
+                        .collect::<Vec<_>>();
+                    assert_eq!(
+                        index.intersecting_polygons(&segment),
+                        expected,
+                        "{:?}",
+                        segment
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn polygons_around_matches_a_linear_scan() {
+        let mut rng = Rng::new(12);
+        for &size in &[100, 1000, 1 << 40] {
+            for _ in 0..20 {
+                let polygons = polygons(&mut rng, 30, size);
+                let index = ObstacleIndex::new(&polygons);
+                for _ in 0..50 {
+                    let coord = segment(&mut rng, size).a;
+                    let (x, y) = (coord.x.to_f64(), coord.y.to_f64());
+                    let expected = (0..polygons.len())
+                        .filter(|&polygon| index.bounds[polygon].contains(x, y))
+                        .collect::<Vec<_>>();
+                    assert_eq!(index.polygons_around(coord), expected, "{:?}", coord);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn segment_queries_agree_with_the_index() {
+        let mut rng = Rng::new(13);
+        let polygons = polygons(&mut rng, 30, 1000);
+        let index = ObstacleIndex::new(&polygons);
+        for _ in 0..200 {
+            let segment = segment(&mut rng, 1000);
+            let expected = index
+                .intersecting_polygons(&segment)
+                .into_iter()
+                .map(|polygon| polygons[polygon].points.clone())
+                .collect::<Vec<_>>();
+            let found = segment
+                .get_intersecting_polygons(&polygons)
+                .into_iter()
+                .map(|polygon| polygon.points)
+                .collect::<Vec<_>>();
+            assert_eq!(found, expected);
+            assert_eq!(
+                segment.get_intersecting_polygon_coords(&polygons),
+                index.intersecting_polygon_coords(&segment)
+            );
+        }
+    }
+
+    #[test]
+    fn cells_along_a_diagonal_skip_the_rest_of_its_bounding_box() {
+        let polygons = (0..10)
+            .map(|i| {
+                let coord = |x, y| Coord { x, y };
+                Polygon::from_points(vec![
+                    coord(i * 10, i * 10),
+                    coord(i * 10 + 5, i * 10),
+                    coord(i * 10 + 5, i * 10 + 5),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let index = ObstacleIndex::new(&polygons);
+        let diagonal = Segment::from_coords(Coord { x: 0, y: 0 }, Coord { x: 100, y: 100 });
+        let cells = index.cells_along(&diagonal);
+        assert!(cells.len() < index.columns * index.rows / 2);
+    }
+}
@@ -0,0 +1,271 @@
+//! Provides the Intersection enum, classifying how two line segments meet.
+
+use shape::coord::Coord;
+use shape::orientation::Orientation;
+use shape::scalar::Scalar;
+use shape::segment::Segment;
+
+/// Represents how two line segments meet.
+#[derive(Copy, Clone, Debug)]
+pub enum Intersection<T = i64> {
+    /// The segments do not meet.
+    None,
+
+    /// The segments properly cross at a single point in the interior of both.
+    ///
+    /// The crossing generally lies between integer coordinates, so is computed in floating point.
+    Point(Coord<f64>),
+
+    /// An end of one segment lies in the interior of the other, so they touch without crossing.
+    Touching(Coord<T>),
+
+    /// The segments share an end, and meet nowhere else.
+    TouchingEndpoint(Coord<T>),
+
+    /// The segments are colinear and share a stretch of positive length.
+    Overlap(Segment<T>),
+}
+
+impl<T: Scalar> PartialEq for Intersection<T> {
+    fn eq(&self, other: &Intersection<T>) -> bool {
+        match (*self, *other) {
+            (Intersection::None, Intersection::None) => true,
+            (Intersection::Point(p), Intersection::Point(q)) => p == q,
+            (Intersection::Touching(p), Intersection::Touching(q)) => p == q,
+            (Intersection::TouchingEndpoint(p), Intersection::TouchingEndpoint(q)) => p == q,
+            (Intersection::Overlap(p), Intersection::Overlap(q)) => p == q,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Scalar> Intersection<T> {
+    /// Returns true if the segments meet at all.
+    pub fn is_some(&self) -> bool {
+        *self != Intersection::None
+    }
+}
+
+impl<T: Scalar> Segment<T> {
+    /// Classifies how self meets another line segment.
+    ///
+    /// Unlike `intersects`, segments that share an end are reported, as touching or overlapping.
+    /// Segments whose ends are equal are treated as a single point.
+    pub fn intersection(&self, other: &Segment<T>) -> Intersection<T> {
+        let o1 = Orientation::from_coords(self.a, self.b, other.a);
+        let o2 = Orientation::from_coords(self.a, self.b, other.b);
+        let o3 = Orientation::from_coords(other.a, other.b, self.a);
+        let o4 = Orientation::from_coords(other.a, other.b, self.b);
+
+        if o1.is_colinear() && o2.is_colinear() && o3.is_colinear() && o4.is_colinear() {
+            return self.colinear_intersection(other);
+        }
+
+        if !o1.is_colinear()
+            && !o2.is_colinear()
+            && o1 != o2
+            && !o3.is_colinear()
+            && !o4.is_colinear()
+            && o3 != o4
+        {
+            return Intersection::Point(self.crossing_point(other));
+        }
+
+        // At most one end lies on the other segment, as they are not colinear.
+        let touching = [
+            (o1, other.a, *self),
+            (o2, other.b, *self),
+            (o3, self.a, *other),
+            (o4, self.b, *other),
+        ]
+        .iter()
+        .find(|&&(orientation, coord, segment)| {
+            orientation.is_colinear() && segment.contains_colinear_coord(coord)
+        })
+        .map(|&(_, coord, _)| coord);
+        match touching {
+            Some(coord) => self.touching(other, coord),
+            None => Intersection::None,
+        }
+    }
+
+    /// Classifies how self meets another line segment lying on the same line.
+    fn colinear_intersection(&self, other: &Segment<T>) -> Intersection<T> {
+        let (p, q) = ordered(self.a, self.b);
+        let (r, s) = ordered(other.a, other.b);
+        let start = p.max(r);
+        let end = q.min(s);
+        if start > end {
+            Intersection::None
+        } else if start == end {
+            self.touching(other, start)
+        } else {
+            Intersection::Overlap(Segment { a: start, b: end })
+        }
+    }
+
+    /// Classifies a single point where self meets another line segment.
+    fn touching(&self, other: &Segment<T>, coord: Coord<T>) -> Intersection<T> {
+        let end_of = |segment: &Segment<T>| coord == segment.a || coord == segment.b;
+        if end_of(self) && end_of(other) {
+            Intersection::TouchingEndpoint(coord)
+        } else {
+            Intersection::Touching(coord)
+        }
+    }
+
+    /// Computes the point where the lines through self and another line segment cross.
+    fn crossing_point(&self, other: &Segment<T>) -> Coord<f64> {
+        let (ax, ay) = (self.a.x.to_f64(), self.a.y.to_f64());
+        let (dx, dy) = (self.b.x.to_f64() - ax, self.b.y.to_f64() - ay);
+        let (cx, cy) = (other.a.x.to_f64(), other.a.y.to_f64());
+        let (ex, ey) = (other.b.x.to_f64() - cx, other.b.y.to_f64() - cy);
+        let t = ((cx - ax) * ey - (cy - ay) * ex) / (dx * ey - dy * ex);
+        Coord {
+            x: ax + t * dx,
+            y: ay + t * dy,
+        }
+    }
+}
+
+/// Orders two coordinates, which orders them along any line through both.
+fn ordered<T: Scalar>(a: Coord<T>, b: Coord<T>) -> (Coord<T>, Coord<T>) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A segment between two coordinates, kept in the order given.
+    fn segment(a: (i64, i64), b: (i64, i64)) -> Segment {
+        Segment {
+            a: Coord { x: a.0, y: a.1 },
+            b: Coord { x: b.0, y: b.1 },
+        }
+    }
+
+    /// A coordinate.
+    fn coord(x: i64, y: i64) -> Coord {
+        Coord { x, y }
+    }
+
+    #[test]
+    fn intersection_classifies_each_way_segments_meet() {
+        let cases = [
+            // Proper crossings, at and between integer coordinates.
+            (
+                segment((0, 0), (4, 4)),
+                segment((0, 4), (4, 0)),
+                Intersection::Point(Coord { x: 2.0, y: 2.0 }),
+            ),
+            (
+                segment((0, 0), (1, 1)),
+                segment((0, 1), (1, 0)),
+                Intersection::Point(Coord { x: 0.5, y: 0.5 }),
+            ),
+            // T-junctions, an end lying in the interior of the other.
+            (
+                segment((0, 0), (4, 0)),
+                segment((2, 0), (2, 3)),
+                Intersection::Touching(coord(2, 0)),
+            ),
+            (
+                segment((0, 0), (0, 4)),
+                segment((-2, 2), (0, 2)),
+                Intersection::Touching(coord(0, 2)),
+            ),
+            // A shared endpoint, at an angle.
+            (
+                segment((0, 0), (4, 0)),
+                segment((4, 0), (6, 3)),
+                Intersection::TouchingEndpoint(coord(4, 0)),
+            ),
+            // Colinear, touching end to end at one point.
+            (
+                segment((0, 0), (4, 0)),
+                segment((4, 0), (8, 0)),
+                Intersection::TouchingEndpoint(coord(4, 0)),
+            ),
+            // Colinear overlaps, partial, contained and identical.
+            (
+                segment((0, 0), (4, 0)),
+                segment((2, 0), (6, 0)),
+                Intersection::Overlap(segment((2, 0), (4, 0))),
+            ),
+            (
+                segment((0, 0), (6, 6)),
+                segment((2, 2), (4, 4)),
+                Intersection::Overlap(segment((2, 2), (4, 4))),
+            ),
+            (
+                segment((0, 0), (0, 5)),
+                segment((0, 0), (0, 5)),
+                Intersection::Overlap(segment((0, 0), (0, 5))),
+            ),
+            // Colinear but disjoint.
+            (
+                segment((0, 0), (2, 0)),
+                segment((3, 0), (5, 0)),
+                Intersection::None,
+            ),
+            // Parallel, and lines crossing beyond the segments.
+            (
+                segment((0, 0), (4, 0)),
+                segment((0, 1), (4, 1)),
+                Intersection::None,
+            ),
+            (
+                segment((0, 0), (1, 1)),
+                segment((3, 0), (4, -1)),
+                Intersection::None,
+            ),
+            // Zero length segments, a single point in the interior, at an end, and off the other.
+            (
+                segment((2, 0), (2, 0)),
+                segment((0, 0), (4, 0)),
+                Intersection::Touching(coord(2, 0)),
+            ),
+            (
+                segment((4, 0), (4, 0)),
+                segment((0, 0), (4, 0)),
+                Intersection::TouchingEndpoint(coord(4, 0)),
+            ),
+            (
+                segment((2, 1), (2, 1)),
+                segment((0, 0), (4, 0)),
+                Intersection::None,
+            ),
+            (
+                segment((1, 1), (1, 1)),
+                segment((1, 1), (1, 1)),
+                Intersection::TouchingEndpoint(coord(1, 1)),
+            ),
+            (
+                segment((1, 1), (1, 1)),
+                segment((2, 2), (2, 2)),
+                Intersection::None,
+            ),
+        ];
+        for &(first, second, expected) in &cases {
+            let reversed = |segment: Segment| Segment {
+                a: segment.b,
+                b: segment.a,
+            };
+            // The classification does not depend on the order of the segments or their ends.
+            for &(p, q) in &[
+                (first, second),
+                (second, first),
+                (reversed(first), second),
+                (first, reversed(second)),
+            ] {
+                assert_eq!(p.intersection(&q), expected, "{:?} {:?}", p, q);
+                assert_eq!(p.intersection(&q).is_some(), expected != Intersection::None);
+            }
+        }
+    }
+}
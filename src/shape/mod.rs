@@ -4,4 +4,14 @@ pub mod coord;
 pub mod polygon;
 pub mod segment;
 pub mod orientation;
+pub mod scalar;
+pub mod boolean;
+pub mod containment;
 pub mod hull;
+pub mod index;
+pub mod intersection;
+pub mod offset;
+pub mod path;
+pub mod sweep;
+pub mod validation;
+mod wide;
@@ -0,0 +1,320 @@
+//! Provides offsetting of polygons, inflating them by a clearance distance.
+
+use shape::coord::Coord;
+use shape::polygon::Polygon;
+use shape::scalar::Scalar;
+use std::f64::consts::PI;
+
+/// The largest angle turned between consecutive vertices of a round join.
+const ROUND_STEP: f64 = PI / 8.0;
+
+/// How far a mitre may reach from it's vertex, as a multiple of the offset distance, before it
+/// is cut short.
+const MITRE_LIMIT: f64 = 2.0;
+
+/// How the offset edges are joined around convex corners.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Join {
+    /// Joined by an arc around the corner, the Minkowski sum with a disc.
+    #[default]
+    Round,
+
+    /// Joined by extending the edges until they meet, cut short at sharp corners.
+    Mitre,
+}
+
+impl<T: Scalar> Polygon<T> {
+    /// Inflates the polygon, moving every edge outward by a distance and joining them around
+    /// convex corners. It's holes are deflated by the same distance, and dropped if they close.
+    ///
+    /// Every point of the original polygon is at least the distance from the outside of the
+    /// result, round joins are made of straight edges that lie outside the true arc. Integer
+    /// coordinates are rounded to the nearest value, so may fall short by up to half a unit.
+    /// Where the distance exceeds the width of a concavity, the edges either side of it can
+    /// cross.
+    ///
+    /// A negative distance deflates the polygon instead, joining the edges around it's reflex
+    /// corners, and leaves no points if any part of the polygon closes up. A distance of zero
+    /// leaves the polygon unchanged.
+    pub fn offset(&self, distance: f64, join: Join) -> Polygon<T> {
+        let len = self.points.len();
+        if distance.is_nan() || distance == 0.0 || len < 3 {
+            return self.clone();
+        }
+
+        let points = self
+            .points
+            .iter()
+            .map(|coord| (coord.x.to_f64(), coord.y.to_f64()))
+            .collect::<Vec<_>>();
+        // Positive when counterclockwise with the y axis pointing up, so the outward normal lies
+        // to the right of each edge.
+        let area = (0..len)
+            .map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % len]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum::<f64>();
+        // Deflating is inflating with the normals reversed, as if the polygon were wound the
+        // other way.
+        let winding = if (area < 0.0) != (distance < 0.0) {
+            -1.0
+        } else {
+            1.0
+        };
+        let deflating = distance < 0.0;
+        let hole_distance = -distance;
+        let distance = distance.abs();
+        let normal = |i: usize| {
+            let (a, b) = (points[i % len], points[(i + 1) % len]);
+            let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+            let length = dx.hypot(dy);
+            (winding * dy / length, -winding * dx / length)
+        };
+
+        let mut offset = Vec::new();
+        for (i, &vertex) in points.iter().enumerate() {
+            let before = normal(i + len - 1);
+            let after = normal(i);
+            let turn = winding * (before.0 * after.1 - before.1 * after.0);
+            let cosine = before.0 * after.0 + before.1 * after.1;
+            let at = |normal: (f64, f64), scale: f64| {
+                (vertex.0 + normal.0 * scale, vertex.1 + normal.1 * scale)
+            };
+
+            if turn <= 0.0 {
+                // The offset edges cross at a reflex or straight vertex, so meet at their crossing.
+                let mitre = (before.0 + after.0, before.1 + after.1);
+                offset.push(at(mitre, distance / (1.0 + cosine)));
+                continue;
+            }
+
+            match join {
+                Join::Round => {
+                    let sweep = turn.atan2(cosine);
+                    let steps = (sweep / ROUND_STEP).ceil().max(1.0);
+                    let step = sweep / steps;
+                    let start = before.1.atan2(before.0);
+                    offset.push(at(before, distance));
+                    // Vertices where tangents to the arc meet, so the arc lies inside the edges.
+                    let radius = distance / (step / 2.0).cos();
+                    for j in 0..(steps as usize) {
+                        let angle = start + winding * step * (j as f64 + 0.5);
+                        offset.push(at((angle.cos(), angle.sin()), radius));
+                    }
+                    offset.push(at(after, distance));
+                }
+                Join::Mitre => {
+                    let mitre = (before.0 + after.0, before.1 + after.1);
+                    if 2.0 / (1.0 + cosine) <= MITRE_LIMIT * MITRE_LIMIT {
+                        offset.push(at(mitre, distance / (1.0 + cosine)));
+                    } else {
+                        // Cut the mitre square to it's bisector, at the limit.
+                        let length = mitre.0.hypot(mitre.1);
+                        let bisector = (mitre.0 / length, mitre.1 / length);
+                        let along = |normal: (f64, f64)| {
+                            let reach = normal.0 * bisector.0 + normal.1 * bisector.1;
+                            let tangent = (-normal.1, normal.0);
+                            let slope = tangent.0 * bisector.0 + tangent.1 * bisector.1;
+                            let extend = (MITRE_LIMIT - reach) * distance / slope;
+                            (
+                                vertex.0 + normal.0 * distance + tangent.0 * extend,
+                                vertex.1 + normal.1 * distance + tangent.1 * extend,
+                            )
+                        };
+                        offset.push(along(before));
+                        offset.push(along(after));
+                    }
+                }
+            }
+        }
+
+        // Where a deflated polygon closes up, the offset edges pass each other and come back
+        // within the distance of the boundary. Part of it may stay open, but dropping the rest
+        // errs on the side of keeping obstacles solid.
+        let collapsed = deflating
+            && offset.iter().any(|&point| {
+                (0..len).any(|i| {
+                    segment_distance(point, points[i], points[(i + 1) % len])
+                        < distance * (1.0 - 1e-9)
+                })
+            });
+        if collapsed {
+            offset.clear();
+        }
+
+        let mut polygon = Polygon {
+            points: offset
+                .into_iter()
+                .map(|(x, y)| Coord {
+                    x: T::from_f64(x),
+                    y: T::from_f64(y),
+                })
+                .collect(),
+            holes: self
+                .holes
+                .iter()
+                .map(|hole| hole.offset(hole_distance, join))
+                .filter(|hole| hole.points.len() >= 3)
+                .collect(),
+        };
+        polygon.normalize();
+        polygon
+    }
+}
+
+/// The distance from a point to the closest point of a segment.
+fn segment_distance(point: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let along = (point.0 - a.0) * dx + (point.1 - a.1) * dy;
+    let t = if length <= 0.0 || along <= 0.0 {
+        0.0
+    } else if along >= length {
+        1.0
+    } else {
+        along / length
+    };
+    (point.0 - a.0 - t * dx).hypot(point.1 - a.1 - t * dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shape::containment::Containment;
+
+    /// A square with corners at `low` and `high` on both axes, counterclockwise with the y axis
+    /// pointing up.
+    fn square<T: Scalar>(low: T, high: T) -> Polygon<T> {
+        let coord = |x, y| Coord { x, y };
+        Polygon::from_points(vec![
+            coord(low, low),
+            coord(high, low),
+            coord(high, high),
+            coord(low, high),
+        ])
+    }
+
+    /// The points of a polygon in order, whatever vertex it starts from.
+    fn sorted<T: Scalar>(polygon: &Polygon<T>) -> Vec<Coord<T>> {
+        let mut points = polygon.points.clone();
+        points.sort();
+        points
+    }
+
+    /// The distance from a point to the boundary of a polygon.
+    fn boundary_distance(polygon: &Polygon<f64>, point: Coord<f64>) -> f64 {
+        let len = polygon.points.len();
+        (0..len)
+            .map(|i| {
+                let (a, b) = (polygon.points[i], polygon.points[(i + 1) % len]);
+                segment_distance((point.x, point.y), (a.x, a.y), (b.x, b.y))
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn mitre_join_expands_a_square_exactly() {
+        let offset = square(0, 10).offset(2.0, Join::Mitre);
+        assert_eq!(sorted(&offset), sorted(&square(-2, 12)));
+        assert!(offset.holes.is_empty());
+    }
+
+    #[test]
+    fn clockwise_input_is_inflated_outward() {
+        let mut clockwise = square(0, 10);
+        clockwise.points.reverse();
+        for &join in &[Join::Round, Join::Mitre] {
+            let expected = square(0, 10).offset(2.0, join);
+            let offset = clockwise.offset(2.0, join);
+            // Both come out normalized, so wound the same way.
+            assert_eq!(offset.points, expected.points);
+        }
+    }
+
+    #[test]
+    fn round_join_stays_outside_the_arc() {
+        let (original, distance) = (square(0.0, 10.0), 2.0);
+        let offset = original.offset(distance, Join::Round);
+        // Each right angle is turned in four steps, with a vertex either side of them.
+        assert_eq!(offset.points.len(), 4 * 6);
+        let furthest = distance / (ROUND_STEP / 2.0).cos();
+        for &point in &offset.points {
+            let reach = boundary_distance(&original, point);
+            assert!(reach >= distance * (1.0 - 1e-9), "{:?}", point);
+            assert!(reach <= furthest * (1.0 + 1e-9), "{:?}", point);
+        }
+        // Every point at the distance around a corner lies within the result.
+        for corner in &original.points {
+            for step in 0..=90 {
+                let angle = f64::from(step) * PI / 180.0;
+                let (dx, dy) = (angle.cos(), angle.sin());
+                let (sx, sy) = ((corner.x - 5.0).signum(), (corner.y - 5.0).signum());
+                let point = Coord {
+                    x: corner.x + sx * dx * distance * (1.0 - 1e-9),
+                    y: corner.y + sy * dy * distance * (1.0 - 1e-9),
+                };
+                assert_eq!(offset.contains(point), Containment::Inside, "{:?}", point);
+            }
+        }
+    }
+
+    #[test]
+    fn mitre_join_is_cut_at_sharp_corners() {
+        let coord = |x, y| Coord { x, y };
+        // A long thin triangle, with a sharp corner at it's tip and blunt ones at it's base.
+        let original =
+            Polygon::from_points(vec![coord(0.0, 0.0), coord(100.0, 0.0), coord(0.0, 10.0)]);
+        let distance = 1.0;
+        let offset = original.offset(distance, Join::Mitre);
+        // The tip is cut into two vertices, the base corners each keep one.
+        assert_eq!(offset.points.len(), 4);
+        for &point in &offset.points {
+            let reach = boundary_distance(&original, point);
+            assert!(reach >= distance * (1.0 - 1e-9), "{:?}", point);
+        }
+        // Uncut, the tip would reach about twenty times the distance beyond it's vertex along the
+        // bisector of the corner, but it is cut square to the bisector at the limit.
+        let tip = coord(100.0, 0.0);
+        let unit = |from: Coord<f64>| {
+            let (dx, dy) = (tip.x - from.x, tip.y - from.y);
+            (dx / dx.hypot(dy), dy / dx.hypot(dy))
+        };
+        let (first, second) = (unit(coord(0.0, 0.0)), unit(coord(0.0, 10.0)));
+        let length = (first.0 + second.0).hypot(first.1 + second.1);
+        let bisector = ((first.0 + second.0) / length, (first.1 + second.1) / length);
+        let reaches = offset
+            .points
+            .iter()
+            .map(|point| (point.x - tip.x) * bisector.0 + (point.y - tip.y) * bisector.1)
+            .filter(|&reach| reach > 0.0)
+            .collect::<Vec<_>>();
+        assert_eq!(reaches.len(), 2);
+        for reach in reaches {
+            assert!((reach - MITRE_LIMIT * distance).abs() < 1e-9, "{}", reach);
+        }
+    }
+
+    #[test]
+    fn holes_deflate_and_drop_when_they_close() {
+        let mut polygon = square(0, 20);
+        polygon.holes.push(square(5, 15));
+        polygon.holes.push(square(16, 19));
+        polygon.normalize();
+        let offset = polygon.offset(2.0, Join::Mitre);
+        assert_eq!(sorted(&offset), sorted(&square(-2, 22)));
+        // The wide hole shrinks by the distance on every side, the narrow one closes.
+        assert_eq!(offset.holes.len(), 1);
+        assert_eq!(sorted(&offset.holes[0]), sorted(&square(7, 13)));
+    }
+
+    #[test]
+    fn negative_distance_deflates_and_zero_leaves_the_polygon() {
+        let original = square(0, 10);
+        let deflated = original.offset(-2.0, Join::Round);
+        assert_eq!(sorted(&deflated), sorted(&square(2, 8)));
+        assert!(original.offset(-6.0, Join::Round).points.is_empty());
+        assert_eq!(original.offset(0.0, Join::Round).points, original.points);
+    }
+}
@@ -1,5 +1,8 @@
 //! Provides the Orientation enum.
+use error::Error;
 use shape::coord::Coord;
+use shape::scalar::Scalar;
+use std::cmp::Ordering;
 
 /// Represents the orientation of three points.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -15,11 +18,36 @@ pub enum Orientation {
 }
 impl Orientation {
     /// Computes an orientation from coordinates.
-    pub fn from_coords(p: Coord, q: Coord, r: Coord) -> Orientation {
-        match (q.y - p.y) * (r.x - q.x) - (q.x - p.x) * (r.y - q.y) {
-            n if n < 0 => Orientation::Clockwise,
-            n if n > 0 => Orientation::Counterclockwise,
-            _ => Orientation::Colinear,
+    ///
+    /// Exact over the full range of integer coordinates. For floating point coordinates, points
+    /// within rounding error of colinear are treated as colinear, see `Scalar::determinant_sign`.
+    pub fn from_coords<T: Scalar>(p: Coord<T>, q: Coord<T>, r: Coord<T>) -> Orientation {
+        Orientation::from_sign(T::determinant_sign(
+            [p.y, q.y],
+            [q.x, r.x],
+            [p.x, q.x],
+            [q.y, r.y],
+        ))
+    }
+
+    /// Computes an orientation from coordinates, reporting an error if the orientation
+    /// determinant overflows the coordinate type itself.
+    pub fn checked_from_coords<T: Scalar>(
+        p: Coord<T>,
+        q: Coord<T>,
+        r: Coord<T>,
+    ) -> Result<Orientation, Error> {
+        T::checked_determinant([p.y, q.y], [q.x, r.x], [p.x, q.x], [q.y, r.y])
+            .ok_or(Error::Overflow)?;
+        Ok(Orientation::from_coords(p, q, r))
+    }
+
+    /// Converts the sign of an orientation determinant into an orientation.
+    fn from_sign(sign: Ordering) -> Orientation {
+        match sign {
+            Ordering::Less => Orientation::Clockwise,
+            Ordering::Greater => Orientation::Counterclockwise,
+            Ordering::Equal => Orientation::Colinear,
         }
     }
 
@@ -38,3 +66,140 @@ impl Orientation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigInt;
+    use shape::wide::WideI64;
+    use testing::Rng;
+
+    /// The orientation of three points computed with arbitrary precision, and whether every step
+    /// of computing it's determinant fits in an `i64`.
+    fn orientation(p: Coord, q: Coord, r: Coord) -> (Orientation, bool) {
+        let fits =
+            |value: &BigInt| BigInt::from(i64::MIN) <= *value && *value <= BigInt::from(i64::MAX);
+        let difference = |a: i64, b: i64| BigInt::from(b) - BigInt::from(a);
+        let (a, b) = (difference(p.y, q.y), difference(q.x, r.x));
+        let (c, d) = (difference(p.x, q.x), difference(q.y, r.y));
+        let (left, right) = (&a * &b, &c * &d);
+        let determinant = &left - &right;
+        let steps = [&a, &b, &c, &d, &left, &right, &determinant];
+        let fits = steps.iter().all(|&step| fits(step));
+        let orientation = Orientation::from_sign(determinant.cmp(&BigInt::from(0)));
+        (orientation, fits)
+    }
+
+    /// A random coordinate, mostly near the extremes of `i64`.
+    fn coord(rng: &mut Rng) -> Coord {
+        Coord {
+            x: rng.extreme(),
+            y: rng.extreme(),
+        }
+    }
+
+    #[test]
+    fn from_coords_matches_arbitrary_precision() {
+        let mut rng = Rng::new(1);
+        for _ in 0..100_000 {
+            let (p, q, r) = (coord(&mut rng), coord(&mut rng), coord(&mut rng));
+            assert_eq!(Orientation::from_coords(p, q, r), orientation(p, q, r).0);
+        }
+    }
+
+    #[test]
+    fn from_coords_matches_arbitrary_precision_when_colinear() {
+        let mut rng = Rng::new(2);
+        for _ in 0..100_000 {
+            // Points along a line through `p` with a small step, reaching far across the range.
+            let p = coord(&mut rng);
+            let (dx, dy) = (rng.range(-3, 3), rng.range(-3, 3));
+            let along = |steps: i64| Coord {
+                x: p.x.wrapping_add(dx.wrapping_mul(steps)),
+                y: p.y.wrapping_add(dy.wrapping_mul(steps)),
+            };
+            let q = along(rng.range(-1 << 60, 1 << 60));
+            let mut r = along(rng.range(-1 << 60, 1 << 60));
+            // Nudge some off the line by the smallest amount.
+            if rng.range(0, 1) == 0 {
+                r.x = r.x.wrapping_add(1);
+            }
+            assert_eq!(Orientation::from_coords(p, q, r), orientation(p, q, r).0);
+        }
+    }
+
+    #[test]
+    fn from_coords_matches_arbitrary_precision_for_i32() {
+        let mut rng = Rng::new(3);
+        for _ in 0..100_000 {
+            let mut coord = || Coord {
+                x: rng.extreme() as i32,
+                y: rng.extreme() as i32,
+            };
+            let (p, q, r) = (coord(), coord(), coord());
+            let widen = |coord: Coord<i32>| Coord {
+                x: i64::from(coord.x),
+                y: i64::from(coord.y),
+            };
+            assert_eq!(
+                Orientation::from_coords(p, q, r),
+                orientation(widen(p), widen(q), widen(r)).0
+            );
+        }
+    }
+
+    #[test]
+    fn from_coords_matches_arbitrary_precision_for_the_wide_type() {
+        let mut rng = Rng::new(5);
+        // Values across the range the wide type is stored in, mostly near its extremes and zero.
+        let mut value = || {
+            let offset = rng.range(0, 1000) as i128;
+            WideI64(match rng.range(0, 3) {
+                0 => (i128::from(rng.next_u64()) << 64) | i128::from(rng.next_u64()),
+                1 => i128::MAX - offset,
+                2 => i128::MIN + offset,
+                _ => offset - 500,
+            })
+        };
+        for _ in 0..100_000 {
+            let (p, q, r) = (
+                Coord {
+                    x: value(),
+                    y: value(),
+                },
+                Coord {
+                    x: value(),
+                    y: value(),
+                },
+                Coord {
+                    x: value(),
+                    y: value(),
+                },
+            );
+            let difference = |a: WideI64, b: WideI64| BigInt::from(b.0) - BigInt::from(a.0);
+            let determinant = difference(p.y, q.y) * difference(q.x, r.x)
+                - difference(p.x, q.x) * difference(q.y, r.y);
+            let expected = Orientation::from_sign(determinant.cmp(&BigInt::from(0)));
+            assert_eq!(Orientation::from_coords(p, q, r), expected);
+        }
+    }
+
+    #[test]
+    fn checked_from_coords_reports_overflow_exactly() {
+        let mut rng = Rng::new(4);
+        for _ in 0..100_000 {
+            let (p, q, r) = (coord(&mut rng), coord(&mut rng), coord(&mut rng));
+            let (expected, fits) = orientation(p, q, r);
+            match Orientation::checked_from_coords(p, q, r) {
+                Ok(orientation) => {
+                    assert!(fits);
+                    assert_eq!(orientation, expected);
+                }
+                Err(error) => {
+                    assert_eq!(error, Error::Overflow);
+                    assert!(!fits);
+                }
+            }
+        }
+    }
+}
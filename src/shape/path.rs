@@ -0,0 +1,81 @@
+//! Provides the Path struct.
+
+use shape::coord::Coord;
+use shape::hull::Hull;
+use shape::scalar::Scalar;
+use shape::segment::Segment;
+
+/// Represents a walkable path between two points.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Path<T = i64> {
+    /// The points along the path, ordered from origin to destination.
+    pub points: Vec<Coord<T>>,
+
+    /// The total euclidean length of the path.
+    pub length: f64,
+}
+
+impl<T: Scalar> Path<T> {
+    /// Constructs a path from it's ordered points, computing it's length.
+    pub fn from_points(points: Vec<Coord<T>>) -> Path<T> {
+        let length = points
+            .iter()
+            .zip(points.iter().skip(1))
+            .map(|(&a, &b)| distance(a, b))
+            .sum();
+        Path { points, length }
+    }
+
+    /// Extracts the shortest path from origin to destination along the boundary of a hull.
+    ///
+    /// Walks both boundary chains of the hull from the origin and returns the shorter. Returns
+    /// `None` if either the origin or destination does not lie on the hull boundary.
+    pub fn from_hull(hull: &Hull<T>, origin: Coord<T>, destination: Coord<T>) -> Option<Path<T>> {
+        if origin == destination {
+            return Some(Path::from_points(vec![origin]));
+        }
+
+        let mut ring = hull.vertices().to_vec();
+        split_ring_at(&mut ring, origin);
+        split_ring_at(&mut ring, destination);
+
+        let start = ring.iter().position(|&coord| coord == origin)?;
+        let end = ring.iter().position(|&coord| coord == destination)?;
+
+        let len = ring.len();
+        let forward = (0..((end + len - start) % len + 1))
+            .map(|i| ring[(start + i) % len])
+            .collect::<Vec<_>>();
+        let backward = (0..((start + len - end) % len + 1))
+            .map(|i| ring[(start + len - i) % len])
+            .collect::<Vec<_>>();
+
+        let forward = Path::from_points(forward);
+        let backward = Path::from_points(backward);
+        if backward.length < forward.length {
+            Some(backward)
+        } else {
+            Some(forward)
+        }
+    }
+}
+
+/// The euclidean distance between two coordinates.
+pub fn distance<T: Scalar>(a: Coord<T>, b: Coord<T>) -> f64 {
+    let dx = b.x.to_f64() - a.x.to_f64();
+    let dy = b.y.to_f64() - a.y.to_f64();
+    dx.hypot(dy)
+}
+
+/// Inserts a coordinate into a ring if it lies along one of the ring's edges.
+fn split_ring_at<T: Scalar>(ring: &mut Vec<Coord<T>>, coord: Coord<T>) {
+    if ring.contains(&coord) {
+        return;
+    }
+    let len = ring.len();
+    if let Some(i) = (0..len)
+        .find(|&i| Segment::from_coords(ring[i], ring[(i + 1) % len]).contains_colinear_coord(coord))
+    {
+        ring.insert(i + 1, coord);
+    }
+}
@@ -1,24 +1,86 @@
 //! Provides the Polygon struct.
 
+use shape::containment::Containment;
 use shape::coord::Coord;
+use shape::scalar::Scalar;
 use shape::segment::Segment;
+use std::hash::{Hash, Hasher};
 
-/// Represents a polygon.
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, Hash)]
-pub struct Polygon {
+/// Represents a polygon, with any number of holes cut out of it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Polygon<T = i64> {
     /// The set a points that make up the polygon, ordered counterclockwise.
     #[serde(rename = "point")]
-    pub points: Vec<Coord>,
+    pub points: Vec<Coord<T>>,
+
+    /// The holes inside the polygon, each ordered clockwise. Holes of holes are ignored.
+    #[serde(
+        rename = "hole",
+        default = "Vec::new",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub holes: Vec<Polygon<T>>,
+}
+
+impl<T: Scalar> PartialEq for Polygon<T> {
+    fn eq(&self, other: &Polygon<T>) -> bool {
+        self.points == other.points && self.holes == other.holes
+    }
+}
+
+impl<T: Scalar> Eq for Polygon<T> {}
+
+impl<T: Scalar> Hash for Polygon<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.points.hash(state);
+        self.holes.hash(state);
+    }
 }
 
-impl Polygon {
-    /// Returns a vector of the segments that make up the polygon.
-    pub fn segments(&self) -> Vec<Segment> {
+impl<T: Scalar> Polygon<T> {
+    /// Constructs a polygon without holes from it's points.
+    pub fn from_points(points: Vec<Coord<T>>) -> Polygon<T> {
+        Polygon {
+            points,
+            holes: Vec::new(),
+        }
+    }
+
+    /// Returns a vector of the segments that make up the polygon, those of the outer boundary
+    /// first, then those of each hole.
+    pub fn segments(&self) -> Vec<Segment<T>> {
+        let mut edges = self.boundary_segments();
+        for hole in &self.holes {
+            edges.extend(hole.boundary_segments());
+        }
+        edges
+    }
+
+    /// Returns a vector of the segments that make up the outer boundary of the polygon.
+    pub fn boundary_segments(&self) -> Vec<Segment<T>> {
         let cycleiter = self.points.iter().chain(self.points.iter().take(1));
-        let cycleiter2 = self.points.iter().chain(self.points.iter().take(2)).skip(1);
+        let cycleiter2 = self.points.iter().chain(self.points.iter().take(1)).skip(1);
         let edges = cycleiter
             .zip(cycleiter2)
             .map(|(&a, &b)| Segment::from_coords(a, b));
         edges.collect::<Vec<_>>()
     }
+
+    /// Returns the polygon with every coordinate of it and it's holes doubled, in the wide type
+    /// of the coordinates so it cannot overflow.
+    ///
+    /// The midpoint of two coordinates is their sum in doubled coordinates, see `Coord::sum`, so
+    /// it can be classified exactly against the doubled polygon, even for integer coordinates.
+    pub fn doubled(&self) -> Polygon<T::Wide> {
+        Polygon {
+            points: self.points.iter().map(|&coord| coord.sum(coord)).collect(),
+            holes: self.holes.iter().map(Polygon::doubled).collect(),
+        }
+    }
+
+    /// Checks if a coordinate lies strictly inside the polygon and outside all of it's holes,
+    /// coordinates on the boundary of the polygon or a hole are not enclosed.
+    pub fn encloses(&self, coord: Coord<T>) -> bool {
+        self.contains(coord) == Containment::Inside
+    }
 }
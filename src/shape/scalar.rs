@@ -0,0 +1,194 @@
+//! Provides the Scalar trait, abstracting over the numeric types coordinates can be made of.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use shape::wide::{compare_products, WideI64};
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Mul, Sub};
+
+/// A numeric type that can be used for coordinates.
+///
+/// Coordinates are shared between threads when legs are processed in parallel.
+pub trait Scalar:
+    Copy
+    + Send
+    + Sync
+    + Debug
+    + PartialEq
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Serialize
+    + DeserializeOwned
+{
+    /// A type that holds the sum of any two values exactly, so midpoints can be found in doubled
+    /// coordinates. The wide type of `i64` is internal to the crate, see `shape::wide`.
+    type Wide: Scalar;
+
+    /// The additive identity.
+    fn zero() -> Self;
+
+    /// Converts the value to the wide type, exactly.
+    fn widen(self) -> Self::Wide;
+
+    /// The absolute value.
+    fn abs(self) -> Self;
+
+    /// The absolute value, returning `None` if it overflows.
+    fn checked_abs(self) -> Option<Self>;
+
+    /// Converts the value to a float, possibly losing precision.
+    fn to_f64(self) -> f64;
+
+    /// Converts a float to the value, rounding to the nearest integer for integer types.
+    fn from_f64(value: f64) -> Self;
+
+    /// A total order over values, used to give coordinates equality, ordering and hashing.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Feeds the value into a hasher, consistently with `total_cmp`.
+    fn hash_scalar<H: Hasher>(&self, state: &mut H);
+
+    /// Returns the sign of `(a[1] - a[0]) * (b[1] - b[0]) - (c[1] - c[0]) * (d[1] - d[0])`.
+    ///
+    /// Integer types compute this exactly over their full range, without overflow. Floating
+    /// point types treat any difference within the rounding error of the products as zero, so
+    /// nearly colinear points are colinear.
+    fn determinant_sign(a: [Self; 2], b: [Self; 2], c: [Self; 2], d: [Self; 2]) -> Ordering;
+
+    /// Computes `(a[1] - a[0]) * (b[1] - b[0]) - (c[1] - c[0]) * (d[1] - d[0])` in the type
+    /// itself, returning `None` if any step overflows.
+    fn checked_determinant(a: [Self; 2], b: [Self; 2], c: [Self; 2], d: [Self; 2]) -> Option<Self>;
+}
+
+macro_rules! impl_integer_scalar {
+    ($($t:ty => $wide:ty),*) => {$(
+        impl Scalar for $t {
+            type Wide = $wide;
+
+            fn zero() -> Self {
+                0
+            }
+
+            fn widen(self) -> $wide {
+                <$wide>::from(self)
+            }
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn checked_abs(self) -> Option<Self> {
+                <$t>::checked_abs(self)
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn from_f64(value: f64) -> Self {
+                value.round() as $t
+            }
+
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                self.cmp(other)
+            }
+
+            fn hash_scalar<H: Hasher>(&self, state: &mut H) {
+                self.hash(state)
+            }
+
+            fn determinant_sign(
+                a: [Self; 2],
+                b: [Self; 2],
+                c: [Self; 2],
+                d: [Self; 2],
+            ) -> Ordering {
+                let difference = |pair: [Self; 2]| {
+                    (pair[1].cmp(&pair[0]), pair[1].abs_diff(pair[0]) as u128)
+                };
+                compare_products(difference(a), difference(b), difference(c), difference(d))
+            }
+
+            fn checked_determinant(
+                a: [Self; 2],
+                b: [Self; 2],
+                c: [Self; 2],
+                d: [Self; 2],
+            ) -> Option<Self> {
+                let difference = |pair: [Self; 2]| pair[1].checked_sub(pair[0]);
+                let left = difference(a)?.checked_mul(difference(b)?)?;
+                let right = difference(c)?.checked_mul(difference(d)?)?;
+                left.checked_sub(right)
+            }
+        }
+    )*}
+}
+
+impl_integer_scalar!(i32 => i64, i64 => WideI64);
+
+/// The relative error bound used when classifying the sign of a floating point difference.
+const F64_RELATIVE_EPSILON: f64 = 3.3306690738754716e-16;
+
+impl Scalar for f64 {
+    type Wide = f64;
+
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn widen(self) -> f64 {
+        self
+    }
+
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+
+    fn checked_abs(self) -> Option<Self> {
+        Some(f64::abs(self))
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        // Adding zero folds negative zero into positive zero.
+        (self + 0.0).total_cmp(&(other + 0.0))
+    }
+
+    fn hash_scalar<H: Hasher>(&self, state: &mut H) {
+        (self + 0.0).to_bits().hash(state)
+    }
+
+    fn determinant_sign(a: [Self; 2], b: [Self; 2], c: [Self; 2], d: [Self; 2]) -> Ordering {
+        let left = (a[1] - a[0]) * (b[1] - b[0]);
+        let right = (c[1] - c[0]) * (d[1] - d[0]);
+        let difference = left - right;
+        let bound = F64_RELATIVE_EPSILON * (left.abs() + right.abs());
+        if difference > bound {
+            Ordering::Greater
+        } else if difference < -bound {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        }
+    }
+
+    fn checked_determinant(a: [Self; 2], b: [Self; 2], c: [Self; 2], d: [Self; 2]) -> Option<Self> {
+        let determinant = (a[1] - a[0]) * (b[1] - b[0]) - (c[1] - c[0]) * (d[1] - d[0]);
+        if determinant.is_finite() {
+            Some(determinant)
+        } else {
+            None
+        }
+    }
+}
@@ -1,45 +1,77 @@
 //! Provides the Segment struct.
 
+use error::Error;
+use shape::containment::Contact;
 use shape::coord::Coord;
-use std::cmp::max;
-use std::cmp::min;
+use shape::index::ObstacleIndex;
 use shape::orientation::Orientation;
-use std::collections::HashSet;
 use shape::polygon::Polygon;
+use shape::scalar::Scalar;
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
 
 /// Represents a line segment AB.
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub struct Segment {
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct Segment<T = i64> {
     /// The left start of the line segment.
-    pub a: Coord,
+    pub a: Coord<T>,
 
     /// The right end of the line segment.
-    pub b: Coord,
+    pub b: Coord<T>,
 }
-impl Segment {
+
+impl<T: Scalar> PartialEq for Segment<T> {
+    fn eq(&self, other: &Segment<T>) -> bool {
+        self.a == other.a && self.b == other.b
+    }
+}
+
+impl<T: Scalar> Eq for Segment<T> {}
+
+impl<T: Scalar> PartialOrd for Segment<T> {
+    fn partial_cmp(&self, other: &Segment<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders segments by their left end, then their right end.
+impl<T: Scalar> Ord for Segment<T> {
+    fn cmp(&self, other: &Segment<T>) -> Ordering {
+        self.a.cmp(&other.a).then_with(|| self.b.cmp(&other.b))
+    }
+}
+
+impl<T: Scalar> Hash for Segment<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.a.hash(state);
+        self.b.hash(state);
+    }
+}
+
+impl<T: Scalar> Segment<T> {
     /// Constructs a line segment from it's coordinates AB.
-    pub fn from_coords(a: Coord, b: Coord) -> Segment {
-        let (a, b) = match (a.x.cmp(&b.x), a.y.cmp(&b.y)) {
-            (Ordering::Less, _) | (Ordering::Equal, Ordering::Less) => (a, b),
-            _ => (b, a),
-        };
-        Segment { a, b }
+    pub fn from_coords(a: Coord<T>, b: Coord<T>) -> Segment<T> {
+        if a < b {
+            Segment { a, b }
+        } else {
+            Segment { a: b, b: a }
+        }
     }
 
     /// Checks if a point lies on self.
-    pub fn contains_colinear_coord(&self, coord: Coord) -> bool {
+    pub fn contains_colinear_coord(&self, coord: Coord<T>) -> bool {
         if Orientation::from_coords(self.a, self.b, coord).is_colinear() {
-            (coord.x <= max(self.a.x, self.b.x) && coord.x >= min(self.a.x, self.b.x)
-                && coord.y <= max(self.a.y, self.b.y)
-                && coord.y >= min(self.a.y, self.b.y))
+            between(coord.x, self.a.x, self.b.x) && between(coord.y, self.a.y, self.b.y)
         } else {
             false
         }
     }
 
     /// Checks if self intersects another line segment.
-    pub fn intersects(&self, other: &Segment) -> bool {
+    ///
+    /// Segments that share an end are never reported, see `intersection` to classify them.
+    pub fn intersects(&self, other: &Segment<T>) -> bool {
         if self.a == other.a || self.a == other.b || self.b == other.a || self.b == other.b {
             return false;
         }
@@ -79,40 +111,150 @@ impl Segment {
     }
 
     /// Returns a value proportional to the distance between the line (extended
-    /// of the segment) and the points.
-    pub fn coord_distance(&self, other: Coord) -> i64 {
-        ((other.y - self.a.y) * (self.b.x - self.a.x)
-            - (self.b.y - self.a.y) * (other.x - self.a.x))
-            .abs()
-    }
-
-    /// Finds the polygons that intersect with a segment.
-    pub fn get_intersecting_polygons(&self, polygons: &[Polygon]) -> HashSet<Polygon> {
-        let mut intersecting_polygons = HashSet::new();
-        'p: for polygon in polygons.iter() {
-            for polygon_segment in polygon.segments() {
-                if polygon_segment.intersects(self) {
-                    intersecting_polygons.insert(polygon.clone());
-                    continue 'p;
+    /// of the segment) and the points, reporting an error if it overflows.
+    ///
+    /// This is computed in the coordinate type, see `compare_coord_distance` to compare distances
+    /// exactly without overflow.
+    pub fn checked_coord_distance(&self, other: Coord<T>) -> Result<T, Error> {
+        T::checked_determinant(
+            [self.a.y, other.y],
+            [self.a.x, self.b.x],
+            [self.a.y, self.b.y],
+            [self.a.x, other.x],
+        )
+        .and_then(Scalar::checked_abs)
+        .ok_or(Error::Overflow)
+    }
+
+    /// Exactly compares the distances of two points from the line (extended of the segment).
+    ///
+    /// Both points must lie on the same side of the line, or on it.
+    pub fn compare_coord_distance(&self, first: Coord<T>, second: Coord<T>) -> Ordering {
+        let side = match Orientation::from_coords(self.a, self.b, first) {
+            Orientation::Colinear => Orientation::from_coords(self.a, self.b, second),
+            orientation => orientation,
+        };
+        // The difference of the signed distances, which only needs coordinate differences.
+        let difference = T::determinant_sign(
+            [second.y, first.y],
+            [self.a.x, self.b.x],
+            [self.a.y, self.b.y],
+            [second.x, first.x],
+        );
+        match side {
+            Orientation::Counterclockwise => difference.reverse(),
+            _ => difference,
+        }
+    }
+
+    /// Checks if the segment passes through the interior of a polygon, see `Polygon::contact`.
+    ///
+    /// Segments that only touch or run along the boundary do not cross the interior.
+    pub fn crosses_interior(&self, polygon: &Polygon<T>) -> bool {
+        polygon.contact(self) == Contact::Penetrating
+    }
+
+    /// Finds the polygons whose interior the segment passes through, in the order they are given.
+    ///
+    /// This builds an index over the polygons for the one query, so to query many segments
+    /// against the same polygons, build an `ObstacleIndex` once instead.
+    pub fn get_intersecting_polygons(&self, polygons: &[Polygon<T>]) -> Vec<Polygon<T>> {
+        ObstacleIndex::new(polygons)
+            .intersecting_polygons(self)
+            .into_iter()
+            .map(|polygon| polygons[polygon].clone())
+            .collect()
+    }
+
+    /// Finds the coordinates of polygons whose interior the segment passes through.
+    ///
+    /// This builds an index over the polygons for the one query, see `get_intersecting_polygons`.
+    pub fn get_intersecting_polygon_coords(&self, polygons: &[Polygon<T>]) -> BTreeSet<Coord<T>> {
+        ObstacleIndex::new(polygons).intersecting_polygon_coords(self)
+    }
+}
+
+/// Checks if a value lies between two bounds, in either order.
+fn between<T: Scalar>(value: T, a: T, b: T) -> bool {
+    (a <= value && value <= b) || (b <= value && value <= a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigInt;
+    use testing::Rng;
+
+    /// The value of `(a[1] - a[0]) * (b[1] - b[0]) - (c[1] - c[0]) * (d[1] - d[0])`, and whether
+    /// every step of computing it fits in an `i64`.
+    fn determinant(a: [i64; 2], b: [i64; 2], c: [i64; 2], d: [i64; 2]) -> (BigInt, bool) {
+        let fits =
+            |value: &BigInt| BigInt::from(i64::MIN) <= *value && *value <= BigInt::from(i64::MAX);
+        let difference = |pair: [i64; 2]| BigInt::from(pair[1]) - BigInt::from(pair[0]);
+        let (a, b, c, d) = (difference(a), difference(b), difference(c), difference(d));
+        let (left, right) = (&a * &b, &c * &d);
+        let value = &left - &right;
+        let steps = [&a, &b, &c, &d, &left, &right, &value];
+        let fits = steps.iter().all(|&step| fits(step));
+        (value, fits)
+    }
+
+    /// A random coordinate, mostly near the extremes of `i64`.
+    fn coord(rng: &mut Rng) -> Coord {
+        Coord {
+            x: rng.extreme(),
+            y: rng.extreme(),
+        }
+    }
+
+    #[test]
+    fn checked_coord_distance_matches_arbitrary_precision() {
+        let mut rng = Rng::new(5);
+        for _ in 0..100_000 {
+            let (a, b, point) = (coord(&mut rng), coord(&mut rng), coord(&mut rng));
+            let segment = Segment { a, b };
+            let (value, fits) = determinant([a.y, point.y], [a.x, b.x], [a.y, b.y], [a.x, point.x]);
+            let value = if value < BigInt::from(0) {
+                -value
+            } else {
+                value
+            };
+            match segment.checked_coord_distance(point) {
+                Ok(distance) => {
+                    assert!(fits);
+                    assert_eq!(BigInt::from(distance), value);
+                }
+                Err(Error::Overflow) => {
+                    assert!(!fits || value > BigInt::from(i64::MAX));
                 }
+                Err(error) => panic!("unexpected error {}", error),
             }
         }
-        intersecting_polygons
-    }
-
-    /// Finds the coordinates of polygons that intersect the segment.
-    pub fn get_intersecting_polygon_coords(&self, polygons: &[Polygon]) -> HashSet<Coord> {
-        let mut intersecting_polygons = HashSet::new();
-        'p: for polygon in polygons.iter() {
-            for polygon_segment in polygon.segments() {
-                if polygon_segment.intersects(self) {
-                    for coord in &polygon.points {
-                        intersecting_polygons.insert(coord.clone());
-                    }
-                    continue 'p;
-                }
+    }
+
+    #[test]
+    fn compare_coord_distance_matches_arbitrary_precision() {
+        let mut rng = Rng::new(6);
+        let mut compared = 0;
+        while compared < 100_000 {
+            let (a, b) = (coord(&mut rng), coord(&mut rng));
+            let (first, second) = (coord(&mut rng), coord(&mut rng));
+            // The signed distances of each point from the line.
+            let (first_side, _) =
+                determinant([a.y, first.y], [a.x, b.x], [a.y, b.y], [a.x, first.x]);
+            let (second_side, _) =
+                determinant([a.y, second.y], [a.x, b.x], [a.y, b.y], [a.x, second.x]);
+            let zero = BigInt::from(0);
+            if (first_side < zero && second_side > zero)
+                || (first_side > zero && second_side < zero)
+            {
+                continue;
             }
+            let magnitude = |side: BigInt| if side < zero { -side } else { side };
+            let expected = magnitude(first_side).cmp(&magnitude(second_side));
+            let segment = Segment { a, b };
+            assert_eq!(segment.compare_coord_distance(first, second), expected);
+            compared += 1;
         }
-        intersecting_polygons
     }
 }
@@ -0,0 +1,676 @@
+//! Provides a sweep line over many line segments, reporting every pair that meets without testing
+//! each pair.
+//!
+//! This is the Bentley–Ottmann algorithm. A vertical line sweeps from left to right, stopping at
+//! the ends of segments and where they cross, while the segments it currently cuts are kept in
+//! order from bottom to top. Segments can only meet once they are next to each other in that
+//! order, so only neighbours are tested, and reporting the `k` pairs among `n` segments takes
+//! `O((n + k) log n)` time.
+//!
+//! The segments cut by the sweep are kept in a balanced tree ordered by position alone, see
+//! `Status`. Segments are compared with a stop as the tree is descended, but never with each
+//! other, which cannot be done exactly where they cross, so each crossing swaps two neighbours in
+//! place instead. Finding, inserting and removing a segment and finding it's neighbours each take
+//! `O(log n)` expected time.
+//!
+//! Every comparison is exact, except the order in which crossings are visited, as crossings
+//! generally lie between coordinates.
+
+use shape::containment::Containment;
+use shape::coord::Coord;
+use shape::intersection::Intersection;
+use shape::orientation::Orientation;
+use shape::polygon::Polygon;
+use shape::scalar::Scalar;
+use shape::segment::Segment;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+
+/// The orientation of a point above a line segment, looking from it's left end to it's right.
+const ABOVE: Orientation = Orientation::Clockwise;
+
+/// Finds the pairs of line segments that meet, as `Segment::intersection` would find, in
+/// ascending order.
+///
+/// Each pair holds the lower index first. Segments that share an end, touch or overlap are
+/// reported as well as those that cross.
+pub fn intersecting_pairs<T: Scalar>(segments: &[Segment<T>]) -> Vec<(usize, usize)> {
+    Sweep::new(segments).run()
+}
+
+/// Finds the pairs of polygons whose boundaries meet, including the boundaries of their holes,
+/// in ascending order.
+///
+/// Each pair holds the lower index first.
+pub fn meeting_polygons<T: Scalar>(polygons: &[Polygon<T>]) -> Vec<(usize, usize)> {
+    let mut owners = Vec::new();
+    let mut edges = Vec::new();
+    for (index, polygon) in polygons.iter().enumerate() {
+        for edge in polygon.segments() {
+            owners.push(index);
+            edges.push(edge);
+        }
+    }
+    intersecting_pairs(&edges)
+        .into_iter()
+        .map(|(first, second)| (owners[first], owners[second]))
+        .filter(|&(first, second)| first != second)
+        .map(|(first, second)| (first.min(second), first.max(second)))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Finds the pairs of polygons whose boundaries meet, or where one lies inside the other, in
+/// ascending order.
+///
+/// Each pair holds the lower index first. A polygon lying inside a hole of another, without
+/// meeting it, is not inside it.
+pub fn overlapping_polygons<T: Scalar>(polygons: &[Polygon<T>]) -> Vec<(usize, usize)> {
+    let mut pairs = meeting_polygons(polygons)
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+
+    // Without meeting, a polygon lies inside another if any one of it's points does.
+    let bounds = polygons.iter().map(bounds).collect::<Vec<_>>();
+    let inside = |outer: usize, inner: usize| {
+        nests(bounds[outer], bounds[inner])
+            && polygons[inner]
+                .points
+                .iter()
+                .take(1)
+                .any(|&point| polygons[outer].contains(point) == Containment::Inside)
+    };
+    // Only polygons whose bounding box starts within that of another can lie inside it, so with
+    // the polygons ordered by the left of their bounding box, each is tested against a run.
+    let mut by_left = (0..polygons.len()).collect::<Vec<_>>();
+    by_left.sort_by(|&a, &b| bounds[a].0.total_cmp(&bounds[b].0));
+    for &outer in &by_left {
+        let (left, right) = (bounds[outer].0, bounds[outer].2);
+        let start = by_left.partition_point(|&index| bounds[index].0 < left);
+        let end = by_left.partition_point(|&index| bounds[index].0 <= right);
+        for &inner in &by_left[start..end] {
+            let pair = (outer.min(inner), outer.max(inner));
+            if outer != inner && !pairs.contains(&pair) && inside(outer, inner) {
+                pairs.insert(pair);
+            }
+        }
+    }
+    pairs.into_iter().collect()
+}
+
+/// The state of the sweep.
+struct Sweep<T> {
+    /// The segments, each ordered from it's left end to it's right.
+    segments: Vec<Segment<T>>,
+
+    /// The crossings ahead of the sweep.
+    crossings: BinaryHeap<Crossing>,
+
+    /// The segments cut by the sweep, from bottom to top.
+    status: Status,
+
+    /// The pairs found to meet.
+    pairs: BTreeSet<(usize, usize)>,
+}
+
+impl<T: Scalar> Sweep<T> {
+    /// Prepares to sweep over a set of segments.
+    fn new(segments: &[Segment<T>]) -> Sweep<T> {
+        let segments = segments
+            .iter()
+            .map(|segment| Segment::from_coords(segment.a, segment.b))
+            .collect::<Vec<_>>();
+        Sweep {
+            status: Status::new(segments.len()),
+            segments,
+            crossings: BinaryHeap::new(),
+            pairs: BTreeSet::new(),
+        }
+    }
+
+    /// Sweeps over every stop, returning the pairs found.
+    fn run(mut self) -> Vec<(usize, usize)> {
+        // The points the sweep stops at, with the segments starting at each.
+        let mut stops = BTreeMap::new();
+        for (index, segment) in self.segments.iter().enumerate() {
+            stops.entry(segment.a).or_insert_with(Vec::new).push(index);
+            stops.entry(segment.b).or_insert_with(Vec::new);
+        }
+
+        for (point, starting) in stops {
+            let position = (point.x.to_f64(), point.y.to_f64());
+            while self
+                .crossings
+                .peek()
+                .map(|crossing| crossing.position() <= position)
+                .unwrap_or(false)
+            {
+                let crossing = self.crossings.pop().unwrap(); // Guarunteed to have value
+                self.cross(crossing);
+            }
+            self.stop(point, &starting);
+        }
+        while let Some(crossing) = self.crossings.pop() {
+            self.cross(crossing);
+        }
+        self.pairs.into_iter().collect()
+    }
+
+    /// Handles the sweep reaching a point where segments start or end.
+    fn stop(&mut self, point: Coord<T>, starting: &[usize]) {
+        // The segments cut by the sweep that pass through or end at the point lie together.
+        let segments = &self.segments;
+        let first = self.status.first_where_not(|index| {
+            Orientation::from_coords(segments[index].a, segments[index].b, point) == ABOVE
+        });
+        let mut through = Vec::new();
+        let mut above = first;
+        while let Some(index) = above {
+            let segment = self.segments[index];
+            if !Orientation::from_coords(segment.a, segment.b, point).is_colinear() {
+                break;
+            }
+            through.push(index);
+            above = self.status.next(index);
+        }
+        let below = match first {
+            Some(first) => self.status.previous(first),
+            None => self.status.last(),
+        };
+
+        // Every segment at the point meets every other there.
+        let at_point = through.iter().chain(starting).cloned().collect::<Vec<_>>();
+        for (position, &first) in at_point.iter().enumerate() {
+            for &second in &at_point[(position + 1)..] {
+                self.report(first, second);
+            }
+        }
+
+        // Those that continue past the point are reordered by their direction from it, as they
+        // are just after it.
+        let mut continuing = at_point
+            .into_iter()
+            .filter(|&index| self.segments[index].b != point)
+            .collect::<Vec<_>>();
+        continuing.sort_by(|&first, &second| {
+            match Orientation::from_coords(point, self.segments[first].b, self.segments[second].b) {
+                ABOVE => Ordering::Less,
+                Orientation::Colinear => first.cmp(&second),
+                _ => Ordering::Greater,
+            }
+        });
+        for &index in &through {
+            self.status.remove(index);
+        }
+        for &index in &continuing {
+            self.status.insert_before(index, above);
+        }
+
+        match (continuing.first(), continuing.last()) {
+            (Some(&lowest), Some(&highest)) => {
+                self.neighbours(below, Some(lowest));
+                self.neighbours(Some(highest), above);
+            }
+            _ => self.neighbours(below, above),
+        }
+    }
+
+    /// Handles the sweep reaching the crossing of two neighbours, swapping their order.
+    fn cross(&mut self, crossing: Crossing) {
+        // The pair may have been parted, or already reordered where a segment ended.
+        if !self.status.contains(crossing.lower)
+            || self.status.next(crossing.lower) != Some(crossing.upper)
+        {
+            return;
+        }
+        self.status.swap(crossing.lower, crossing.upper);
+        let below = self.status.previous(crossing.upper);
+        self.neighbours(below, Some(crossing.upper));
+        let above = self.status.next(crossing.lower);
+        self.neighbours(Some(crossing.lower), above);
+    }
+
+    /// Tests a segment in the status with the one above it, scheduling their crossing if it lies
+    /// ahead.
+    fn neighbours(&mut self, lower: Option<usize>, upper: Option<usize>) {
+        let (lower, upper) = match (lower, upper) {
+            (Some(lower), Some(upper)) => (lower, upper),
+            _ => return,
+        };
+        let (first, second) = (self.segments[lower], self.segments[upper]);
+        match first.intersection(&second) {
+            Intersection::None => {}
+            Intersection::Point(point) => {
+                self.report(lower, upper);
+                // Crossings behind the sweep have already been passed.
+                if Orientation::from_coords(second.a, second.b, first.b) == ABOVE {
+                    self.crossings.push(Crossing {
+                        x: point.x,
+                        y: point.y,
+                        lower,
+                        upper,
+                    });
+                }
+            }
+            _ => self.report(lower, upper),
+        }
+    }
+
+    /// Records a pair of segments if they meet.
+    fn report(&mut self, first: usize, second: usize) {
+        if first != second
+            && self.segments[first]
+                .intersection(&self.segments[second])
+                .is_some()
+        {
+            self.pairs.insert((first.min(second), first.max(second)));
+        }
+    }
+}
+
+/// The segments cut by the sweep, from bottom to top.
+///
+/// This is a treap, a binary tree kept balanced by giving each node a random priority and keeping
+/// every node's priority above it's children's. It is ordered by position alone, rather than by
+/// comparing segments, and each segment is found through the node holding it, with neighbours
+/// found by walking the tree.
+struct Status {
+    /// The nodes of the tree, including those no longer used.
+    nodes: Vec<Node>,
+
+    /// The node holding each segment, if it is cut by the sweep.
+    node_of: Vec<Option<usize>>,
+
+    /// The root of the tree.
+    root: Option<usize>,
+
+    /// The nodes no longer used, to be reused.
+    free: Vec<usize>,
+
+    /// The state of the generator of priorities, a xorshift generator so sweeps are repeatable.
+    state: u64,
+}
+
+/// A node of the status tree.
+struct Node {
+    /// The segment the node holds.
+    segment: usize,
+
+    /// The priority of the node, above that of it's children.
+    priority: u64,
+
+    /// The parent of the node, if it is not the root.
+    parent: Option<usize>,
+
+    /// The children of the node, below and above it.
+    children: [Option<usize>; 2],
+}
+
+/// The side of a node that lies below it, lower in the status.
+const DOWN: usize = 0;
+
+/// The side of a node that lies above it, higher in the status.
+const UP: usize = 1;
+
+impl Status {
+    /// Creates an empty status for a number of segments.
+    fn new(count: usize) -> Status {
+        Status {
+            nodes: Vec::new(),
+            node_of: vec![None; count],
+            root: None,
+            free: Vec::new(),
+            state: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    /// Checks if a segment is cut by the sweep.
+    fn contains(&self, segment: usize) -> bool {
+        self.node_of[segment].is_some()
+    }
+
+    /// Finds the lowest segment for which a predicate fails, which must hold for every segment
+    /// below some point and none above it.
+    fn first_where_not<F: Fn(usize) -> bool>(&self, predicate: F) -> Option<usize> {
+        let mut first = None;
+        let mut node = self.root;
+        while let Some(current) = node {
+            if predicate(self.nodes[current].segment) {
+                node = self.nodes[current].children[UP];
+            } else {
+                first = Some(current);
+                node = self.nodes[current].children[DOWN];
+            }
+        }
+        first.map(|node| self.nodes[node].segment)
+    }
+
+    /// Finds the highest segment.
+    fn last(&self) -> Option<usize> {
+        self.root
+            .map(|root| self.nodes[self.extreme(root, UP)].segment)
+    }
+
+    /// Finds the segment just below another.
+    fn previous(&self, segment: usize) -> Option<usize> {
+        self.step(segment, DOWN)
+    }
+
+    /// Finds the segment just above another.
+    fn next(&self, segment: usize) -> Option<usize> {
+        self.step(segment, UP)
+    }
+
+    /// Finds the segment next to another on a side.
+    fn step(&self, segment: usize, side: usize) -> Option<usize> {
+        let mut node = self.node_of[segment].expect("segment is cut by the sweep");
+        if let Some(child) = self.nodes[node].children[side] {
+            return Some(self.nodes[self.extreme(child, 1 - side)].segment);
+        }
+        // Climb until coming up from the other side.
+        while let Some(parent) = self.nodes[node].parent {
+            if self.nodes[parent].children[side] != Some(node) {
+                return Some(self.nodes[parent].segment);
+            }
+            node = parent;
+        }
+        None
+    }
+
+    /// Finds the node furthest to a side beneath a node.
+    fn extreme(&self, mut node: usize, side: usize) -> usize {
+        while let Some(child) = self.nodes[node].children[side] {
+            node = child;
+        }
+        node
+    }
+
+    /// Inserts a segment just below another, or above every segment if there is none.
+    fn insert_before(&mut self, segment: usize, above: Option<usize>) {
+        let priority = self.priority();
+        let node = Node {
+            segment,
+            priority,
+            parent: None,
+            children: [None, None],
+        };
+        let node = match self.free.pop() {
+            Some(free) => {
+                self.nodes[free] = node;
+                free
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        self.node_of[segment] = Some(node);
+
+        // Attach the node as a leaf, just below the node above it or above the highest.
+        let place = match above.map(|above| self.node_of[above].expect("segment is cut")) {
+            Some(above) => match self.nodes[above].children[DOWN] {
+                Some(child) => Some((self.extreme(child, UP), UP)),
+                None => Some((above, DOWN)),
+            },
+            None => self.root.map(|root| (self.extreme(root, UP), UP)),
+        };
+        match place {
+            Some((parent, side)) => {
+                self.nodes[parent].children[side] = Some(node);
+                self.nodes[node].parent = Some(parent);
+            }
+            None => self.root = Some(node),
+        }
+
+        // Raise it above any parents of lower priority.
+        while let Some(parent) = self.nodes[node].parent {
+            if self.nodes[parent].priority >= priority {
+                break;
+            }
+            self.rotate(node);
+        }
+    }
+
+    /// Removes a segment.
+    fn remove(&mut self, segment: usize) {
+        let node = self.node_of[segment]
+            .take()
+            .expect("segment is cut by the sweep");
+        // Lower it beneath the children of higher priority until it is a leaf.
+        loop {
+            let child = match self.nodes[node].children {
+                [None, None] => break,
+                [Some(child), None] | [None, Some(child)] => child,
+                [Some(below), Some(above)] => {
+                    if self.nodes[below].priority > self.nodes[above].priority {
+                        below
+                    } else {
+                        above
+                    }
+                }
+            };
+            self.rotate(child);
+        }
+        self.replace(node, None);
+        self.free.push(node);
+    }
+
+    /// Exchanges two segments that are next to each other.
+    fn swap(&mut self, first: usize, second: usize) {
+        let first_node = self.node_of[first].expect("segment is cut by the sweep");
+        let second_node = self.node_of[second].expect("segment is cut by the sweep");
+        self.nodes[first_node].segment = second;
+        self.nodes[second_node].segment = first;
+        self.node_of.swap(first, second);
+    }
+
+    /// Rotates a node above it's parent, keeping the order of the tree.
+    fn rotate(&mut self, node: usize) {
+        let parent = self.nodes[node].parent.expect("node has a parent");
+        let side = if self.nodes[parent].children[DOWN] == Some(node) {
+            DOWN
+        } else {
+            UP
+        };
+        let inner = self.nodes[node].children[1 - side];
+        self.nodes[parent].children[side] = inner;
+        if let Some(inner) = inner {
+            self.nodes[inner].parent = Some(parent);
+        }
+        self.replace(parent, Some(node));
+        self.nodes[node].children[1 - side] = Some(parent);
+        self.nodes[parent].parent = Some(node);
+    }
+
+    /// Replaces a node in it's parent, or as the root, leaving it's own links.
+    fn replace(&mut self, node: usize, with: Option<usize>) {
+        let parent = self.nodes[node].parent;
+        match parent {
+            Some(parent) => {
+                let side = if self.nodes[parent].children[DOWN] == Some(node) {
+                    DOWN
+                } else {
+                    UP
+                };
+                self.nodes[parent].children[side] = with;
+            }
+            None => self.root = with,
+        }
+        if let Some(with) = with {
+            self.nodes[with].parent = parent;
+        }
+    }
+
+    /// Generates the next priority.
+    fn priority(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+/// The crossing of two segments that are neighbours in the status.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Crossing {
+    x: f64,
+    y: f64,
+    lower: usize,
+    upper: usize,
+}
+
+impl Crossing {
+    /// The position of the crossing, in the order the sweep reaches points.
+    fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+}
+
+impl Eq for Crossing {}
+
+/// Orders crossings so the first the sweep reaches is popped first.
+impl Ord for Crossing {
+    fn cmp(&self, other: &Crossing) -> Ordering {
+        other
+            .position()
+            .partial_cmp(&self.position())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| (other.lower, other.upper).cmp(&(self.lower, self.upper)))
+    }
+}
+
+impl PartialOrd for Crossing {
+    fn partial_cmp(&self, other: &Crossing) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The bounding box of a polygon, as minimum and maximum x and y.
+fn bounds<T: Scalar>(polygon: &Polygon<T>) -> (f64, f64, f64, f64) {
+    polygon.points.iter().fold(
+        (
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(min_x, min_y, max_x, max_y), coord| {
+            let (x, y) = (coord.x.to_f64(), coord.y.to_f64());
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        },
+    )
+}
+
+/// Checks if one bounding box lies within another.
+fn nests(outer: (f64, f64, f64, f64), inner: (f64, f64, f64, f64)) -> bool {
+    outer.0 <= inner.0 && outer.1 <= inner.1 && inner.2 <= outer.2 && inner.3 <= outer.3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::Rng;
+
+    /// A coordinate on a small grid, so that ends are shared and segments often touch, overlap or
+    /// run vertically.
+    fn coord(rng: &mut Rng) -> Coord {
+        Coord {
+            x: rng.range(0, 12),
+            y: rng.range(0, 12),
+        }
+    }
+
+    #[test]
+    fn intersecting_pairs_matches_brute_force() {
+        let mut rng = Rng::new(23);
+        for count in 0..40 {
+            for _ in 0..20 {
+                let segments = (0..count)
+                    .map(|_| {
+                        let a = coord(&mut rng);
+                        Segment::from_coords(a, coord(&mut rng))
+                    })
+                    .filter(|segment| segment.a != segment.b)
+                    .collect::<Vec<_>>();
+                let expected = (0..segments.len())
+                    .flat_map(|first| {
+                        ((first + 1)..segments.len()).map(move |second| (first, second))
+                    })
+                    .filter(|&(first, second)| {
+                        segments[first].intersection(&segments[second]).is_some()
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(intersecting_pairs(&segments), expected, "{:?}", segments);
+            }
+        }
+    }
+
+    #[test]
+    fn intersecting_pairs_matches_brute_force_on_many_segments() {
+        let mut rng = Rng::new(25);
+        for _ in 0..5 {
+            // Short segments across a wide area, so many are cut by the sweep at once but few meet.
+            let segments = (0..600)
+                .map(|_| {
+                    let a = Coord {
+                        x: rng.range(0, 10_000),
+                        y: rng.range(0, 10_000),
+                    };
+                    let b = Coord {
+                        x: a.x + rng.range(-300, 300),
+                        y: a.y + rng.range(-300, 300),
+                    };
+                    Segment::from_coords(a, b)
+                })
+                .filter(|segment| segment.a != segment.b)
+                .collect::<Vec<_>>();
+            let expected = (0..segments.len())
+                .flat_map(|first| ((first + 1)..segments.len()).map(move |second| (first, second)))
+                .filter(|&(first, second)| {
+                    segments[first].intersection(&segments[second]).is_some()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(intersecting_pairs(&segments), expected);
+        }
+    }
+
+    #[test]
+    fn overlapping_polygons_matches_brute_force() {
+        let mut rng = Rng::new(24);
+        for _ in 0..50 {
+            // Squares of varied sizes, some nested, some meeting and some apart.
+            let polygons = (0..20)
+                .map(|_| {
+                    let (x, y, size) = (rng.range(0, 60), rng.range(0, 60), rng.range(1, 30));
+                    let corner = |x, y| Coord { x, y };
+                    Polygon::from_points(vec![
+                        corner(x, y),
+                        corner(x + size, y),
+                        corner(x + size, y + size),
+                        corner(x, y + size),
+                    ])
+                })
+                .collect::<Vec<_>>();
+            let meets = |first: &Polygon, second: &Polygon| {
+                first.segments().iter().any(|a| {
+                    second
+                        .segments()
+                        .iter()
+                        .any(|b| a.intersection(b).is_some())
+                })
+            };
+            let inside = |outer: &Polygon, inner: &Polygon| {
+                outer.contains(inner.points[0]) == Containment::Inside
+            };
+            let expected = (0..polygons.len())
+                .flat_map(|first| ((first + 1)..polygons.len()).map(move |second| (first, second)))
+                .filter(|&(first, second)| {
+                    let (a, b) = (&polygons[first], &polygons[second]);
+                    meets(a, b) || inside(a, b) || inside(b, a)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(overlapping_polygons(&polygons), expected);
+        }
+    }
+}
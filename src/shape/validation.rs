@@ -0,0 +1,387 @@
+//! Provides validation and normalization of polygons.
+
+use shape::coord::Coord;
+use shape::orientation::Orientation;
+use shape::polygon::Polygon;
+use shape::scalar::Scalar;
+use shape::segment::Segment;
+use shape::sweep::{intersecting_pairs, meeting_polygons};
+use std::collections::BTreeSet;
+
+/// The problems found when validating a polygon.
+///
+/// Vertex `i` is `points[i]`, and edge `i` runs from vertex `i` to the vertex after it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ValidationReport {
+    /// The polygon has fewer than three points.
+    pub too_few_points: bool,
+
+    /// The winding order of the polygon, `Colinear` if it encloses no area.
+    pub winding: Orientation,
+
+    /// The vertices that are equal to the vertex after them.
+    pub duplicate_vertices: Vec<usize>,
+
+    /// The vertices at which the boundary doubles back on itself along a line.
+    pub colinear_spikes: Vec<usize>,
+
+    /// The pairs of non adjacent edges that intersect or touch.
+    pub self_intersections: Vec<(usize, usize)>,
+
+    /// The holes with duplicate vertices or colinear spikes, or wound counterclockwise.
+    pub unnormalized_holes: Vec<usize>,
+
+    /// The holes with fewer than three points, that intersect themselves, touch the boundary or
+    /// another hole, or do not lie inside the boundary.
+    pub invalid_holes: Vec<usize>,
+}
+
+impl ValidationReport {
+    /// Checks if the polygon had no problems.
+    pub fn is_valid(&self) -> bool {
+        !self.too_few_points
+            && self.winding == Orientation::Counterclockwise
+            && self.duplicate_vertices.is_empty()
+            && self.colinear_spikes.is_empty()
+            && self.self_intersections.is_empty()
+            && self.unnormalized_holes.is_empty()
+            && self.invalid_holes.is_empty()
+    }
+
+    /// Checks if the polygon has problems that `Polygon::normalize` cannot fix.
+    pub fn is_unfixable(&self) -> bool {
+        self.too_few_points || !self.self_intersections.is_empty() || !self.invalid_holes.is_empty()
+    }
+}
+
+impl<T: Scalar> Polygon<T> {
+    /// Checks the polygon for problems, reporting all that are found.
+    pub fn validate(&self) -> ValidationReport {
+        let len = self.points.len();
+        let vertex = |i: usize| self.points[i % len];
+
+        let duplicate_vertices = (0..len)
+            .filter(|&i| len > 1 && vertex(i) == vertex(i + 1))
+            .collect();
+
+        let colinear_spikes = (0..len)
+            .filter(|&i| len > 2 && is_spike(vertex(i + len - 1), vertex(i), vertex(i + 1)))
+            .collect();
+
+        // Zero length edges are reported as duplicates, and skipped so the edges either side of
+        // them are still considered adjacent.
+        let edges = (0..len)
+            .filter(|&i| vertex(i) != vertex(i + 1))
+            .collect::<Vec<_>>();
+        let segments = edges
+            .iter()
+            .map(|&i| Segment::from_coords(vertex(i), vertex(i + 1)))
+            .collect::<Vec<_>>();
+        let self_intersections = intersecting_pairs(&segments)
+            .into_iter()
+            .filter(|&(k, l)| l >= k + 2 && !(k == 0 && l == edges.len() - 1))
+            .map(|(k, l)| (edges[k], edges[l]))
+            .collect();
+
+        let mut unnormalized_holes = Vec::new();
+        let mut invalid_holes = Vec::new();
+        let boundary = Polygon::from_points(self.points.clone());
+        // The rings are the boundary, then each hole.
+        let rings = Some(boundary.clone())
+            .into_iter()
+            .chain(
+                self.holes
+                    .iter()
+                    .map(|hole| Polygon::from_points(hole.points.clone())),
+            )
+            .collect::<Vec<_>>();
+        let meeting = meeting_polygons(&rings)
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        for (index, hole) in self.holes.iter().enumerate() {
+            let report = hole.validate();
+            if !report.duplicate_vertices.is_empty()
+                || !report.colinear_spikes.is_empty()
+                || report.winding == Orientation::Counterclockwise
+            {
+                unnormalized_holes.push(index);
+            }
+
+            let ring = &rings[index + 1];
+            let crosses =
+                |other: usize| meeting.contains(&(other.min(index + 1), other.max(index + 1)));
+            // Without crossings, a ring lies inside another if any one of it's points does.
+            let inside = |outer: &Polygon<T>, inner: &Polygon<T>| {
+                inner
+                    .points
+                    .iter()
+                    .take(1)
+                    .any(|&point| outer.encloses(point))
+            };
+            let clashes = (0..self.holes.len()).any(|other| {
+                other != index
+                    && (crosses(other + 1)
+                        || inside(&rings[other + 1], ring)
+                        || inside(ring, &rings[other + 1]))
+            });
+            if report.too_few_points
+                || !report.self_intersections.is_empty()
+                || crosses(0)
+                || !inside(&boundary, ring)
+                || clashes
+            {
+                invalid_holes.push(index);
+            }
+        }
+
+        ValidationReport {
+            too_few_points: len < 3,
+            winding: self.winding(),
+            duplicate_vertices,
+            colinear_spikes,
+            self_intersections,
+            unnormalized_holes,
+            invalid_holes,
+        }
+    }
+
+    /// Fixes the problems that can be fixed automatically, removing duplicate vertices and
+    /// colinear spikes, and reversing clockwise polygons to be counterclockwise and
+    /// counterclockwise holes to be clockwise.
+    pub fn normalize(&mut self) {
+        for hole in &mut self.holes {
+            hole.normalize();
+            hole.points.reverse();
+        }
+
+        loop {
+            let len = self.points.len();
+            let removable = (0..len).find(|&i| {
+                let (previous, current, next) = (
+                    self.points[(i + len - 1) % len],
+                    self.points[i],
+                    self.points[(i + 1) % len],
+                );
+                len > 1 && (current == next || (len > 2 && is_spike(previous, current, next)))
+            });
+            match removable {
+                Some(i) => {
+                    self.points.remove(i);
+                }
+                None => break,
+            }
+        }
+
+        if self.winding() == Orientation::Clockwise {
+            self.points.reverse();
+        }
+    }
+
+    /// Computes the winding order from the turn at the lowest, then leftmost, vertex, which must
+    /// be convex for a simple polygon.
+    ///
+    /// Neighbours equal to the vertex are skipped, so duplicates of it do not hide the turn.
+    fn winding(&self) -> Orientation {
+        let len = self.points.len();
+        let lowest = (0..len).min_by(|&i, &j| {
+            let (a, b) = (self.points[i], self.points[j]);
+            a.y.total_cmp(&b.y).then_with(|| a.x.total_cmp(&b.x))
+        });
+        let corner = match lowest {
+            Some(i) => i,
+            None => return Orientation::Colinear,
+        };
+        let distinct = |step: &dyn Fn(usize) -> usize| {
+            (1..len)
+                .map(|offset| self.points[step(offset) % len])
+                .find(|&point| point != self.points[corner])
+        };
+        let previous = distinct(&|offset| corner + len - offset);
+        let next = distinct(&|offset| corner + offset);
+        match (previous, next) {
+            (Some(previous), Some(next)) => {
+                Orientation::from_coords(previous, self.points[corner], next)
+            }
+            _ => Orientation::Colinear,
+        }
+    }
+}
+
+/// Checks if the boundary doubles back on itself at `current`.
+fn is_spike<T: Scalar>(previous: Coord<T>, current: Coord<T>, next: Coord<T>) -> bool {
+    current != previous
+        && current != next
+        && (Segment::from_coords(previous, current).contains_colinear_coord(next)
+            || Segment::from_coords(current, next).contains_colinear_coord(previous))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A polygon without holes from its points.
+    fn polygon(points: &[(i64, i64)]) -> Polygon {
+        Polygon::from_points(points.iter().map(|&(x, y)| Coord { x, y }).collect())
+    }
+
+    /// The report of a polygon without problems.
+    fn valid() -> ValidationReport {
+        ValidationReport {
+            too_few_points: false,
+            winding: Orientation::Counterclockwise,
+            duplicate_vertices: Vec::new(),
+            colinear_spikes: Vec::new(),
+            self_intersections: Vec::new(),
+            unnormalized_holes: Vec::new(),
+            invalid_holes: Vec::new(),
+        }
+    }
+
+    /// A normalized square with a hole.
+    fn with_hole(hole: &[(i64, i64)]) -> Polygon {
+        Polygon {
+            holes: vec![polygon(hole)],
+            ..polygon(&[(0, 0), (0, 10), (10, 10), (10, 0)])
+        }
+    }
+
+    #[test]
+    fn duplicate_lowest_vertex_keeps_the_winding() {
+        let mut square = polygon(&[(0, 0), (0, 0), (10, 0), (10, 10), (0, 10)]);
+        let report = square.validate();
+        assert_eq!(report.winding, Orientation::Clockwise);
+        assert_eq!(report.duplicate_vertices, vec![0]);
+        assert!(!report.is_unfixable());
+
+        square.normalize();
+        assert!(square.validate().is_valid());
+        assert_eq!(square.points.len(), 4);
+    }
+
+    #[test]
+    fn duplicate_lowest_vertex_at_the_wrap_keeps_the_winding() {
+        let report = polygon(&[(0, 0), (0, 10), (10, 10), (10, 0), (0, 0)]).validate();
+        assert_eq!(report.winding, Orientation::Counterclockwise);
+        assert_eq!(report.duplicate_vertices, vec![4]);
+    }
+
+    #[test]
+    fn repeated_point_encloses_no_area() {
+        let report = polygon(&[(3, 3), (3, 3), (3, 3)]).validate();
+        assert_eq!(
+            report,
+            ValidationReport {
+                winding: Orientation::Colinear,
+                duplicate_vertices: vec![0, 1, 2],
+                ..valid()
+            }
+        );
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn crossing_edges_are_reported_in_pairs() {
+        // A bowtie, whose diagonals cross in the middle.
+        let report = polygon(&[(0, 0), (0, 10), (10, 0), (10, 10)]).validate();
+        assert_eq!(
+            report,
+            ValidationReport {
+                self_intersections: vec![(1, 3)],
+                ..valid()
+            }
+        );
+        assert!(report.is_unfixable());
+
+        // A vertex touching an edge that is not next to it.
+        let report = polygon(&[(0, 0), (0, 10), (10, 10), (10, 0), (5, 10)]).validate();
+        assert_eq!(
+            report,
+            ValidationReport {
+                self_intersections: vec![(1, 3), (1, 4)],
+                ..valid()
+            }
+        );
+        assert!(report.is_unfixable());
+    }
+
+    #[test]
+    fn colinear_spikes_are_reported_and_removed() {
+        // The top edge runs past the corner and doubles back to it.
+        let mut spiked = polygon(&[(0, 0), (0, 10), (15, 10), (10, 10), (10, 0)]);
+        assert_eq!(
+            spiked.validate(),
+            ValidationReport {
+                colinear_spikes: vec![2],
+                self_intersections: vec![(1, 3)],
+                ..valid()
+            }
+        );
+
+        spiked.normalize();
+        assert_eq!(spiked.validate(), valid());
+        assert_eq!(spiked, polygon(&[(0, 0), (0, 10), (10, 10), (10, 0)]));
+    }
+
+    #[test]
+    fn hole_outside_the_boundary_is_invalid() {
+        let report = with_hole(&[(20, 20), (25, 20), (25, 25), (20, 25)]).validate();
+        assert_eq!(
+            report,
+            ValidationReport {
+                invalid_holes: vec![0],
+                ..valid()
+            }
+        );
+        assert!(report.is_unfixable());
+    }
+
+    #[test]
+    fn counterclockwise_hole_is_reversed() {
+        let mut polygon = with_hole(&[(2, 2), (2, 8), (8, 8), (8, 2)]);
+        let report = polygon.validate();
+        assert_eq!(
+            report,
+            ValidationReport {
+                unnormalized_holes: vec![0],
+                ..valid()
+            }
+        );
+        assert!(!report.is_unfixable());
+
+        polygon.normalize();
+        assert_eq!(polygon.validate(), valid());
+        assert_eq!(polygon, with_hole(&[(8, 2), (8, 8), (2, 8), (2, 2)]));
+    }
+
+    #[test]
+    fn normalize_reverses_clockwise_polygons() {
+        let mut square = polygon(&[(0, 0), (10, 0), (10, 10), (0, 10)]);
+        assert_eq!(
+            square.validate(),
+            ValidationReport {
+                winding: Orientation::Clockwise,
+                ..valid()
+            }
+        );
+
+        square.normalize();
+        assert_eq!(square.validate(), valid());
+        assert_eq!(square, polygon(&[(0, 10), (10, 10), (10, 0), (0, 0)]));
+    }
+
+    #[test]
+    fn normalize_removes_duplicate_vertices() {
+        let mut square = polygon(&[(0, 0), (0, 10), (0, 10), (10, 10), (10, 0), (0, 0)]);
+        assert_eq!(
+            square.validate(),
+            ValidationReport {
+                duplicate_vertices: vec![1, 5],
+                ..valid()
+            }
+        );
+
+        square.normalize();
+        assert_eq!(square.validate(), valid());
+        assert_eq!(square, polygon(&[(0, 0), (0, 10), (10, 10), (10, 0)]));
+    }
+}
@@ -0,0 +1,127 @@
+//! Provides exact integer arithmetic wider than the coordinate types, kept out of the public
+//! interface.
+
+use shape::scalar::Scalar;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Mul, Sub};
+
+/// The wide type of `i64`, holding sums of `i64` values exactly.
+///
+/// It is not a coordinate type of it's own, only the type `Coord::sum` and `Polygon::doubled`
+/// produce for `i64`, and cannot be named outside of the crate. Each sum adds a single bit to the
+/// 64 of an `i64`, so the 128 bits it is stored in hold sums of sums far beyond any nesting the
+/// crate uses, which is why it can be it's own wide type.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WideI64(pub i128);
+
+impl From<i64> for WideI64 {
+    fn from(value: i64) -> WideI64 {
+        WideI64(i128::from(value))
+    }
+}
+
+impl Add for WideI64 {
+    type Output = WideI64;
+
+    fn add(self, other: WideI64) -> WideI64 {
+        WideI64(self.0 + other.0)
+    }
+}
+
+impl Sub for WideI64 {
+    type Output = WideI64;
+
+    fn sub(self, other: WideI64) -> WideI64 {
+        WideI64(self.0 - other.0)
+    }
+}
+
+impl Mul for WideI64 {
+    type Output = WideI64;
+
+    fn mul(self, other: WideI64) -> WideI64 {
+        WideI64(self.0 * other.0)
+    }
+}
+
+impl Scalar for WideI64 {
+    type Wide = WideI64;
+
+    fn zero() -> Self {
+        WideI64(0)
+    }
+
+    fn widen(self) -> WideI64 {
+        self
+    }
+
+    fn abs(self) -> Self {
+        WideI64(self.0.abs())
+    }
+
+    fn checked_abs(self) -> Option<Self> {
+        self.0.checked_abs().map(WideI64)
+    }
+
+    fn to_f64(self) -> f64 {
+        self.0 as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        WideI64(value.round() as i128)
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    fn hash_scalar<H: Hasher>(&self, state: &mut H) {
+        self.hash(state)
+    }
+
+    fn determinant_sign(a: [Self; 2], b: [Self; 2], c: [Self; 2], d: [Self; 2]) -> Ordering {
+        let difference = |pair: [Self; 2]| (pair[1].cmp(&pair[0]), pair[1].0.abs_diff(pair[0].0));
+        compare_products(difference(a), difference(b), difference(c), difference(d))
+    }
+
+    fn checked_determinant(a: [Self; 2], b: [Self; 2], c: [Self; 2], d: [Self; 2]) -> Option<Self> {
+        let difference = |pair: [Self; 2]| pair[1].0.checked_sub(pair[0].0);
+        let left = difference(a)?.checked_mul(difference(b)?)?;
+        let right = difference(c)?.checked_mul(difference(d)?)?;
+        left.checked_sub(right).map(WideI64)
+    }
+}
+
+/// Compares the products `a * b` and `c * d` exactly.
+///
+/// Each factor is given as its sign, compared with zero, and its magnitude. The factors are
+/// differences of integers of up to 128 bits, so their magnitudes fit in 128 bits unsigned, and
+/// the magnitudes of their products in 256.
+pub fn compare_products(
+    a: (Ordering, u128),
+    b: (Ordering, u128),
+    c: (Ordering, u128),
+    d: (Ordering, u128),
+) -> Ordering {
+    let sign = |ordering: Ordering| ordering as i8;
+    let left = (sign(a.0) * sign(b.0), multiply(a.1, b.1));
+    let right = (sign(c.0) * sign(d.0), multiply(c.1, d.1));
+    match (left.0, right.0) {
+        (1, 1) => left.1.cmp(&right.1),
+        (-1, -1) => right.1.cmp(&left.1),
+        (left_sign, right_sign) => left_sign.cmp(&right_sign),
+    }
+}
+
+/// Multiplies two 128 bit values, returning the high and low halves of the 256 bit product.
+fn multiply(a: u128, b: u128) -> (u128, u128) {
+    const LOW: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & LOW);
+    let (b_high, b_low) = (b >> 64, b & LOW);
+    // The cross terms are each below 2^128, but their sum may carry into bit 128.
+    let (middle, carry) = (a_low * b_high).overflowing_add(a_high * b_low);
+    let (low, low_carry) = (a_low * b_low).overflowing_add(middle << 64);
+    let high = a_high * b_high + (middle >> 64) + ((carry as u128) << 64) + low_carry as u128;
+    (high, low)
+}
@@ -0,0 +1,38 @@
+//! Provides helpers shared by the tests, and by the benches, which include this file.
+
+/// A small deterministic pseudorandom number generator, so that failures can be reproduced.
+pub struct Rng(u64);
+
+impl Rng {
+    /// Seeds a generator.
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// The next value, uniform over every 64 bit value.
+    pub fn next_u64(&mut self) -> u64 {
+        // xorshift64*
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A value between two bounds, including both.
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        let span = (i128::from(high) - i128::from(low) + 1) as u128;
+        (i128::from(low) + (u128::from(self.next_u64()) % span) as i128) as i64
+    }
+
+    /// A value anywhere in the range of `i64`, drawn mostly from near it's extremes and zero,
+    /// where overflow and rounding are likeliest.
+    pub fn extreme(&mut self) -> i64 {
+        let offset = self.range(0, 1000);
+        match self.range(0, 3) {
+            0 => self.next_u64() as i64,
+            1 => i64::MAX - offset,
+            2 => i64::MIN + offset,
+            _ => offset - 500,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Runs every example input through the binary with each planner and hull algorithm, several
+//! times over, checking every run gives the same bytes and that the default output matches the
+//! one checked in under `examples/output`.
+//!
+//! Each run is a process of it's own, so hash sets and maps are seeded differently every time.
+//! Run the tests with and without the `parallel` feature to check both builds give the same
+//! outputs.
+//!
+//! After an intended change to the output, regenerate them with
+//! `convex-hull-pf -p <planner> examples/<name>.toml examples/output/<name>.<planner>.toml`.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// The planners each example is run with, along with the hull algorithms they are run with, the
+/// first of which is the default.
+const PLANNERS: &[(&str, &[&str])] = &[
+    ("hull", &["quickhull", "monotone-chain", "graham", "chan"]),
+    ("visibility", &["quickhull"]),
+];
+
+/// The output modes each example is written in, the first of which is checked in.
+const MODES: &[&str] = &["toml", "json", "geojson"];
+
+/// The number of times each example is run in each configuration.
+const RUNS: usize = 4;
+
+/// Runs the binary on an input, writing the output to stdout.
+fn run(input: &Path, planner: &str, algorithm: &str, mode: &str) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_convex-hull-pf"))
+        .args(["-p", planner, "-a", algorithm, "-o", mode])
+        .arg(input)
+        .arg("-")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{} with the {} planner and {} failed: {}",
+        input.display(),
+        planner,
+        algorithm,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output.stdout
+}
+
+#[test]
+fn examples_match_their_checked_in_outputs_on_every_run() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let mut inputs = fs::read_dir(&examples)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+        .collect::<Vec<_>>();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "no examples in {}", examples.display());
+
+    let mut differing = Vec::new();
+    let mut unstable = Vec::new();
+    for input in &inputs {
+        let name = input.file_stem().unwrap().to_str().unwrap();
+        for &(planner, algorithms) in PLANNERS {
+            for algorithm in algorithms {
+                for mode in MODES {
+                    let first = run(input, planner, algorithm, mode);
+                    for _ in 1..RUNS {
+                        if run(input, planner, algorithm, mode) != first {
+                            unstable.push(format!("{} {} {} {}", name, planner, algorithm, mode));
+                            break;
+                        }
+                    }
+                    if *algorithm != algorithms[0] || *mode != MODES[0] {
+                        continue;
+                    }
+                    let expected_file = examples
+                        .join("output")
+                        .join(format!("{}.{}.toml", name, planner));
+                    let expected = fs::read(&expected_file)
+                        .unwrap_or_else(|error| panic!("{}: {}", expected_file.display(), error));
+                    if first != expected {
+                        differing.push(expected_file);
+                    }
+                }
+            }
+        }
+    }
+    assert!(
+        unstable.is_empty(),
+        "outputs differ between runs: {:?}",
+        unstable
+    );
+    assert!(
+        differing.is_empty(),
+        "outputs differ from their checked in copies: {:?}",
+        differing
+    );
+}